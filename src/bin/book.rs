@@ -0,0 +1,60 @@
+use connect_four::book::{self, Generator};
+use connect_four::EngineOptions;
+
+const USAGE: &str = "Usage: book generate --plies N [--depth D | --solver] [--output FILE]";
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    if args.first().map(String::as_str) != Some("generate") {
+        println!("{USAGE}");
+        return;
+    }
+
+    let mut plies = None;
+    let mut generator = Generator::Search(book::DEFAULT_BOOK_DEPTH);
+    let mut output = book::DEFAULT_BOOK_PATH.to_string();
+    let mut args = args[1..].iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--solver" {
+            generator = Generator::Solver;
+            continue;
+        }
+
+        let value = match args.next() {
+            Some(value) => value,
+            None => {
+                println!("Missing value for {arg}");
+                return;
+            }
+        };
+
+        match (arg.as_str(), value.parse::<usize>()) {
+            ("--plies", Ok(i)) => plies = Some(i),
+            ("--depth", Ok(i)) => generator = Generator::Search(i),
+            ("--output", _) => output = value.clone(),
+            _ => {
+                println!("Invalid option {arg} {value}");
+                return;
+            }
+        }
+    }
+
+    let Some(plies) = plies else {
+        println!("{USAGE}");
+        return;
+    };
+
+    let book = book::generate(
+        plies,
+        &generator,
+        &EngineOptions::default(),
+        |count, ply| println!("{count} positions, ply {ply}"),
+    );
+
+    match book.save(&output) {
+        Ok(_) => println!("Saved {} positions to {}", book.len(), output),
+        Err(error) => println!("Cannot write {output}: {error}"),
+    }
+}
@@ -0,0 +1,524 @@
+mod player;
+mod replay;
+
+use connect_four::book::{self, Book};
+use connect_four::difficulty::Difficulty;
+use connect_four::engine::{self, EngineOptions};
+use connect_four::random::Random;
+use connect_four::rules::{self, Rules};
+use connect_four::{Board, ColumnAnalysis, GameRecord, GameState, Move, Score};
+use player::{Computer, Mode, Player, Players};
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Duration;
+
+const CLEAR_SCREEN: &str = "\x1B[2J\x1B[1;1H";
+const DEFAULT_DELAY_MS: u64 = 1000;
+
+struct Settings<'a> {
+    engine_name: Option<&'a str>,
+    options: EngineOptions,
+    start: Board,
+    mode: Option<Mode>,
+    delay: Duration,
+    show_analysis: bool,
+    save_path: Option<&'a str>,
+}
+
+fn main() {
+    let args = std::env::args().collect::<Vec<_>>();
+
+    if args.get(1).map(String::as_str) == Some("replay") {
+        replay::run(&args[2..]);
+        return;
+    }
+
+    let hash_mb = arg_value(&args, "--hash")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(engine::DEFAULT_HASH_MB);
+    let engine_name = arg_value(&args, "--engine");
+    let show_analysis = args.iter().any(|arg| arg == "--analyze");
+    let save_path = arg_value(&args, "--save");
+
+    let mode = match arg_value(&args, "--mode") {
+        Some(value) => match Mode::parse(value) {
+            Some(mode) => Some(mode),
+            None => {
+                println!("Invalid mode {value}, expected hvc, cvh, hvh or cvc");
+                return;
+            }
+        },
+        None => None,
+    };
+
+    let delay = match arg_value(&args, "--delay").map(str::parse::<u64>) {
+        Some(Ok(ms)) => Duration::from_millis(ms),
+        Some(Err(_)) => {
+            println!("Delay must be a non-negative number of milliseconds");
+            return;
+        }
+        None => Duration::from_millis(DEFAULT_DELAY_MS),
+    };
+
+    if let Some(name) = engine_name {
+        if !engine::names().contains(&name) {
+            println!(
+                "Unknown engine {}, available engines: {}",
+                name,
+                engine::names().join(", ")
+            );
+            return;
+        }
+    }
+
+    let position = arg_value(&args, "--position");
+    let mut rules = Rules::default();
+
+    if let Some(grid) = position {
+        match Board::grid_size(grid) {
+            Ok((width, height)) => rules = Rules::new(width, height),
+            Err(error) => {
+                println!("Invalid starting position: {error}");
+                return;
+            }
+        }
+    }
+
+    if let Some(value) = arg_value(&args, "--size") {
+        match rules::parse_size(value) {
+            Some((width, height)) => rules = Rules::new(width, height),
+            None => {
+                println!("Invalid board size {value}, expected WIDTHxHEIGHT such as 8x7");
+                return;
+            }
+        }
+    }
+
+    if let Some(value) = arg_value(&args, "--connect") {
+        rules.win_length = value.parse().unwrap_or(0);
+
+        if !rules.is_valid() {
+            println!(
+                "Invalid line length {}, expected a number from {} to {}",
+                value,
+                rules::MIN_WIN_LENGTH,
+                rules.width.max(rules.height)
+            );
+            return;
+        }
+    }
+
+    rules.pop_out = args.iter().any(|arg| arg == "--popout");
+
+    let moves = arg_value(&args, "--moves").unwrap_or("");
+    let start = match position {
+        Some(grid) => Board::from_grid_with_rules(grid, rules)
+            .and_then(|mut board| board.play_moves(moves).map(|_| board)),
+        None if rules.is_standard() => Board::from_moves(moves),
+        None => {
+            let mut board = Board::with_rules(rules);
+            board.play_moves(moves).map(|_| board)
+        }
+    };
+
+    let start = match start {
+        Ok(board) => board,
+        Err(error) => {
+            println!("Invalid starting position: {error}");
+            return;
+        }
+    };
+
+    if start.game_over() {
+        println!("{}The game is already over", start);
+        return;
+    }
+
+    let book_path = arg_value(&args, "--book");
+    let book = match book_path {
+        Some(path) => Some(path),
+        None if Path::new(book::DEFAULT_BOOK_PATH).exists() => Some(book::DEFAULT_BOOK_PATH),
+        None => None,
+    }
+    .and_then(|path| match Book::load(path) {
+        Ok(book) => Some(Rc::new(book)),
+        Err(error) => {
+            println!("Cannot load opening book {}: {}", path, error);
+            None
+        }
+    });
+
+    let settings = Settings {
+        engine_name,
+        options: EngineOptions { hash_mb, book },
+        start,
+        mode,
+        delay,
+        show_analysis,
+        save_path,
+    };
+    let mut random = Random::new();
+
+    loop {
+        play_game(&settings, &mut random);
+        std::thread::sleep(Duration::from_secs(5));
+    }
+}
+
+fn play_game(settings: &Settings, random: &mut Random) {
+    let mode = settings.mode.unwrap_or_else(choose_mode);
+    let mut players = Players {
+        red: create_player(mode, true, settings),
+        yellow: create_player(mode, false, settings),
+    };
+    let watching = !players.has_human();
+    let mut board = settings.start.clone();
+    let mut error_message = String::new();
+    let mut analysis_message = String::new();
+    let mut move_message = String::new();
+
+    while !board.game_over() {
+        let turn = board.position().current_player();
+        let name = players.name(turn);
+        let addressed = players.is_human(!turn);
+
+        println!("{}{}", CLEAR_SCREEN, board);
+
+        if let Player::Computer(computer) = players.get_mut(turn) {
+            if watching {
+                print!("{}{}", analysis_message, move_message);
+            }
+
+            println!("{} ({}) is thinking...", name, computer.engine.name());
+
+            if settings.show_analysis {
+                let limits = computer.difficulty.limits(computer.think_time);
+
+                analysis_message = match computer.engine.analyze(&board, &limits) {
+                    Some(score) => format!("{}'s evaluation: {}\n", name, score),
+                    None => String::new(),
+                };
+            }
+
+            let mv = computer.choose_move(&board, random);
+            let reason = connect_four::move_reason(&board, mv);
+            board.play_move(mv).unwrap();
+            move_message = format!("{} played {}: {}\n", name, mv, reason.describe(addressed));
+
+            if watching {
+                std::thread::sleep(settings.delay);
+            }
+
+            continue;
+        }
+
+        let width = board.width();
+        let pop_hint = if board.rules().pop_out {
+            ", p1 to pop"
+        } else {
+            ""
+        };
+        let column = input(format!(
+            "{}{}{}{}'s turn\nEnter column(1-{}{}, u to undo, r to redo, hint, analyze, save/load FILE): ",
+            analysis_message, move_message, error_message, name, width, pop_hint,
+        ));
+
+        if column == "hint" {
+            analysis_message = match connect_four::hint(&board) {
+                Some(hint) => format!("Hint: play {}, {}\n", hint.mv, hint.reason),
+                None => String::new(),
+            };
+            error_message = String::new();
+            continue;
+        }
+
+        if column == "analyze" {
+            analysis_message = column_analysis(&board);
+            error_message = String::new();
+            continue;
+        }
+
+        if let Some(path) = column.strip_prefix("save ") {
+            let record = game_record(&board, &players);
+
+            error_message = match record.save(path.trim()) {
+                Ok(_) => format!("Saved game to {}\n", path.trim()),
+                Err(error) => format!("Cannot save {}: {}\n", path.trim(), error),
+            };
+            continue;
+        }
+
+        if let Some(path) = column.strip_prefix("load ") {
+            error_message = match GameRecord::load(path.trim()).and_then(|record| record.to_board())
+            {
+                Ok(loaded) => {
+                    board = loaded;
+                    String::new()
+                }
+                Err(error) => format!("Cannot load {}: {}\n", path.trim(), error),
+            };
+            analysis_message = String::new();
+            move_message = String::new();
+            continue;
+        }
+
+        if column == "u" || column == "r" {
+            error_message = match column.as_str() {
+                "u" if undo_move(&mut board, &players) => String::new(),
+                "r" if redo_move(&mut board, &players) => String::new(),
+                "u" => String::from("Nothing to undo\n"),
+                _ => String::from("Nothing to redo\n"),
+            };
+            analysis_message = String::new();
+            move_message = String::new();
+            continue;
+        }
+
+        let mv = match Move::parse(&column) {
+            Some(mv) => mv,
+            _ => {
+                error_message = format!("Column must be a number from 1 to {}\n", width);
+                continue;
+            }
+        };
+
+        match board.play_move(mv) {
+            Ok(_) => {
+                error_message = String::new();
+                move_message = String::new();
+            }
+            Err(error) => error_message = format!("Invalid move: {}\n", error),
+        }
+    }
+
+    println!("{}{}", CLEAR_SCREEN, board);
+
+    match board.game_state() {
+        GameState::RedWon => println!("{} Won", players.name(true)),
+        GameState::YellowWon => println!("{} Won", players.name(false)),
+        GameState::Draw => println!("Draw"),
+        GameState::OnGoing => unreachable!(),
+    }
+
+    if let Ok(moves) = board.to_moves() {
+        println!("Moves: {moves}");
+    }
+
+    println!("Position: {}", board.to_grid());
+
+    if let Some(path) = settings.save_path {
+        if let Err(error) = game_record(&board, &players).save(path) {
+            println!("Cannot save {path}: {error}");
+        }
+    }
+}
+
+fn create_player(mode: Mode, turn: bool, settings: &Settings) -> Player {
+    if mode.is_human(turn) {
+        return Player::Human;
+    }
+
+    let color = match (mode, turn) {
+        (Mode::ComputerVsComputer, true) => " for Red",
+        (Mode::ComputerVsComputer, false) => " for Yellow",
+        _ => "",
+    };
+    let difficulty = choose_difficulty(color);
+    let engine_name = settings.engine_name.unwrap_or(difficulty.engine());
+    let think_time = if difficulty == Difficulty::Strong {
+        choose_think_time()
+    } else {
+        None
+    };
+
+    Player::Computer(Computer {
+        engine: engine::create(engine_name, &settings.options).unwrap(),
+        difficulty,
+        think_time,
+    })
+}
+
+// Undo and redo step over computer moves as well, so it's a human's turn again.
+fn undo_move(board: &mut Board, players: &Players) -> bool {
+    if board.undo().is_none() {
+        return false;
+    }
+
+    while !players.is_human(board.position().current_player()) && board.undo().is_some() {}
+
+    true
+}
+
+fn redo_move(board: &mut Board, players: &Players) -> bool {
+    if board.redo().is_none() {
+        return false;
+    }
+
+    while !players.is_human(board.position().current_player()) && board.redo().is_some() {}
+
+    true
+}
+
+fn column_analysis(board: &Board) -> String {
+    let analysis = connect_four::analyze(board);
+    let describe = |column: &ColumnAnalysis| match column.outcome {
+        Some(Score::Win(distance)) => format!("W{distance}"),
+        Some(Score::Loss(distance)) => format!("L{distance}"),
+        Some(Score::Draw) => String::from("D"),
+        None => format!("{:+.0}", column.score * 10.0),
+    };
+
+    let drops = (0..board.width())
+        .map(|col| {
+            let text = analysis
+                .iter()
+                .find(|column| column.mv == Move::Drop(col))
+                .map_or(String::from("-"), describe);
+
+            format!(" {text:^3}")
+        })
+        .collect::<String>();
+    let pops = analysis
+        .iter()
+        .filter(|column| matches!(column.mv, Move::Pop(_)))
+        .map(|column| format!("{} {}", column.mv, describe(column)))
+        .collect::<Vec<_>>();
+    let Some(best) = connect_four::best_analysis(&analysis) else {
+        return String::new();
+    };
+
+    let mut message = format!("{}\n", drops.trim_end());
+
+    if !pops.is_empty() {
+        message += &format!("Pops: {}\n", pops.join(", "));
+    }
+
+    let line = best
+        .pv
+        .iter()
+        .map(Move::to_string)
+        .collect::<Vec<_>>()
+        .join(" ");
+    message += &format!(
+        "Best move {} ({}), expected line {}\nW/L: win/loss in N moves, D: draw, otherwise the evaluation\n",
+        best.mv,
+        describe(best),
+        line
+    );
+
+    message
+}
+
+fn game_record(board: &Board, players: &Players) -> GameRecord {
+    let mut record = GameRecord::new(board);
+
+    for (turn, color) in [(true, "Red"), (false, "Yellow")] {
+        record.set_header(color, players.name(turn));
+
+        if let Player::Computer(computer) = players.get(turn) {
+            record.set_header(&format!("{color}Engine"), computer.engine.name());
+            record.set_header(
+                &format!("{color}Difficulty"),
+                &computer.difficulty.to_string(),
+            );
+        }
+    }
+
+    record
+}
+
+fn choose_mode() -> Mode {
+    let mut error_message = String::new();
+    let options = Mode::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, mode)| format!("{}. {}\n", i + 1, mode))
+        .collect::<String>();
+
+    loop {
+        println!("{}", CLEAR_SCREEN);
+        let choice = input(format!(
+            "{}{}Choose mode(1-{}): ",
+            options,
+            error_message,
+            Mode::ALL.len(),
+        ));
+
+        match choice.parse::<usize>() {
+            Ok(i) if i > 0 && i <= Mode::ALL.len() => return Mode::ALL[i - 1],
+            _ => error_message = format!("Mode must be a number from 1 to {}\n", Mode::ALL.len()),
+        }
+    }
+}
+
+fn choose_difficulty(color: &str) -> Difficulty {
+    let mut error_message = String::new();
+    let options = Difficulty::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, difficulty)| {
+            format!("{}. {} ({})\n", i + 1, difficulty, difficulty.description())
+        })
+        .collect::<String>();
+
+    loop {
+        println!("{}", CLEAR_SCREEN);
+        let choice = input(format!(
+            "{}{}Choose difficulty{}(1-{}): ",
+            options,
+            error_message,
+            color,
+            Difficulty::ALL.len(),
+        ));
+
+        match choice.parse::<usize>() {
+            Ok(i) if i > 0 && i <= Difficulty::ALL.len() => return Difficulty::ALL[i - 1],
+            _ => {
+                error_message = format!(
+                    "Difficulty must be a number from 1 to {}\n",
+                    Difficulty::ALL.len()
+                )
+            }
+        }
+    }
+}
+
+fn choose_think_time() -> Option<Duration> {
+    let mut error_message = String::new();
+
+    loop {
+        println!("{}", CLEAR_SCREEN);
+        let seconds = input(format!(
+            "{}Seconds the computer thinks per move (empty for fixed depth): ",
+            error_message,
+        ));
+
+        if seconds.is_empty() {
+            return None;
+        }
+
+        match seconds.parse::<f64>() {
+            Ok(i) if i > 0.0 && i.is_finite() => return Some(Duration::from_secs_f64(i)),
+            _ => error_message = String::from("Time must be a positive number of seconds\n"),
+        }
+    }
+}
+
+fn arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
+
+fn input(msg: impl fmt::Display) -> String {
+    print!("{msg}");
+    std::io::stdout().flush().unwrap();
+
+    let mut buffer = String::new();
+    std::io::stdin().read_line(&mut buffer).unwrap();
+
+    buffer.trim().to_string()
+}
@@ -0,0 +1,113 @@
+use std::fmt;
+use std::time::Duration;
+
+use connect_four::difficulty::Difficulty;
+use connect_four::random::Random;
+use connect_four::{Board, Engine, Move};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    HumanVsComputer,
+    ComputerVsHuman,
+    HumanVsHuman,
+    ComputerVsComputer,
+}
+
+pub enum Player {
+    Human,
+    Computer(Computer),
+}
+
+pub struct Computer {
+    pub engine: Box<dyn Engine>,
+    pub difficulty: Difficulty,
+    pub think_time: Option<Duration>,
+}
+
+pub struct Players {
+    pub red: Player,
+    pub yellow: Player,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 4] = [
+        Mode::HumanVsComputer,
+        Mode::ComputerVsHuman,
+        Mode::HumanVsHuman,
+        Mode::ComputerVsComputer,
+    ];
+
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "hvc" => Some(Mode::HumanVsComputer),
+            "cvh" => Some(Mode::ComputerVsHuman),
+            "hvh" => Some(Mode::HumanVsHuman),
+            "cvc" => Some(Mode::ComputerVsComputer),
+            _ => None,
+        }
+    }
+
+    pub fn is_human(&self, turn: bool) -> bool {
+        match self {
+            Mode::HumanVsComputer => turn,
+            Mode::ComputerVsHuman => !turn,
+            Mode::HumanVsHuman => true,
+            Mode::ComputerVsComputer => false,
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Mode::HumanVsComputer => "Human vs computer, you move first",
+            Mode::ComputerVsHuman => "Computer vs human, computer moves first",
+            Mode::HumanVsHuman => "Human vs human",
+            Mode::ComputerVsComputer => "Computer vs computer",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+impl Computer {
+    pub fn choose_move(&mut self, board: &Board, random: &mut Random) -> Move {
+        self.difficulty
+            .choose_move(board, self.engine.as_mut(), self.think_time, random)
+    }
+}
+
+impl Players {
+    pub fn get(&self, turn: bool) -> &Player {
+        if turn {
+            &self.red
+        } else {
+            &self.yellow
+        }
+    }
+
+    pub fn get_mut(&mut self, turn: bool) -> &mut Player {
+        if turn {
+            &mut self.red
+        } else {
+            &mut self.yellow
+        }
+    }
+
+    pub fn is_human(&self, turn: bool) -> bool {
+        matches!(self.get(turn), Player::Human)
+    }
+
+    pub fn has_human(&self) -> bool {
+        self.is_human(true) || self.is_human(false)
+    }
+
+    pub fn name(&self, turn: bool) -> &'static str {
+        match (self.is_human(turn), self.is_human(!turn)) {
+            (true, false) => "Player",
+            (false, true) => "Computer",
+            _ if turn => "Red",
+            _ => "Yellow",
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use connect_four::engine::{self, Engine, EngineOptions, SearchLimits};
+use connect_four::{Board, GameRecord, Move};
+
+use crate::{input, CLEAR_SCREEN};
+
+const DEFAULT_ENGINE: &str = "solver";
+const USAGE: &str = "Usage: replay FILE [--analyze] [--engine NAME]";
+
+pub fn run(args: &[String]) {
+    let Some(path) = args.first() else {
+        println!("{USAGE}");
+        return;
+    };
+
+    let mut engine = None;
+    let mut engine_name = DEFAULT_ENGINE;
+    let mut analyze = false;
+    let mut options = args[1..].iter();
+
+    while let Some(arg) = options.next() {
+        match arg.as_str() {
+            "--analyze" => analyze = true,
+            "--engine" => match options.next() {
+                Some(name) => engine_name = name,
+                None => {
+                    println!("Missing value for {arg}");
+                    return;
+                }
+            },
+            _ => {
+                println!("Invalid option {arg}\n{USAGE}");
+                return;
+            }
+        }
+    }
+
+    if analyze {
+        engine = engine::create(engine_name, &EngineOptions::default());
+
+        if engine.is_none() {
+            println!(
+                "Unknown engine {}, available engines: {}",
+                engine_name,
+                engine::names().join(", ")
+            );
+            return;
+        }
+    }
+
+    let record = match GameRecord::load(path) {
+        Ok(record) => record,
+        Err(error) => {
+            println!("Cannot load {path}: {error}");
+            return;
+        }
+    };
+
+    let mut board = match record.to_board() {
+        Ok(board) => board,
+        Err(error) => {
+            println!("Invalid game in {path}: {error}");
+            return;
+        }
+    };
+
+    let length = board.history().len();
+    let mut evaluations = vec![None; length];
+    let mut error_message = String::new();
+
+    while board.undo().is_some() {}
+
+    loop {
+        let ply = board.history().len();
+
+        println!("{}{}", CLEAR_SCREEN, board);
+        println!(
+            "{} vs {}, result {}",
+            record.header("Red").unwrap_or("Red"),
+            record.header("Yellow").unwrap_or("Yellow"),
+            record.header("Result").unwrap_or("*"),
+        );
+
+        if let Some(&mv) = board.history().last() {
+            let mut before = board.clone();
+            before.undo();
+
+            let player = if before.position().current_player() {
+                "Red"
+            } else {
+                "Yellow"
+            };
+            println!("Move {ply}/{length}: {player} played {mv}");
+
+            if let Some(engine) = engine.as_deref_mut() {
+                let evaluation =
+                    evaluations[ply - 1].get_or_insert_with(|| evaluate(engine, &before, mv));
+                println!("{evaluation}");
+            }
+        } else {
+            println!("Start of game, {length} moves");
+        }
+
+        let command = input(format!(
+            "{}Enter n for next, b for back, s for start, e for end, a move number, or q to quit: ",
+            error_message,
+        ));
+        error_message = String::new();
+
+        match command.as_str() {
+            "" | "n" if board.redo().is_none() => error_message = String::from("End of game\n"),
+            "" | "n" => (),
+            "b" if board.undo().is_none() => error_message = String::from("Start of game\n"),
+            "b" => (),
+            "s" => while board.undo().is_some() {},
+            "e" => while board.redo().is_some() {},
+            "q" => return,
+            _ => match command.parse::<usize>() {
+                Ok(target) if target <= length => {
+                    while board.history().len() > target {
+                        board.undo();
+                    }
+
+                    while board.history().len() < target {
+                        board.redo();
+                    }
+                }
+                _ => error_message = format!("Move number must be from 0 to {length}\n"),
+            },
+        }
+    }
+}
+
+fn evaluate(engine: &mut dyn Engine, board: &Board, played: Move) -> String {
+    let limits = SearchLimits::default();
+    let best = engine.choose_move(board, &limits);
+    let score = match engine.analyze(board, &limits) {
+        Some(score) => score.to_string(),
+        None => String::from("no score"),
+    };
+
+    if best == played {
+        format!("{} agrees with this move ({})", engine.name(), score)
+    } else {
+        format!("{} prefers {} ({})", engine.name(), best, score)
+    }
+}
@@ -0,0 +1,170 @@
+use std::io::{self, BufRead};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use connect_four::engine::DEFAULT_HASH_MB;
+use connect_four::{search, Board, Move, Score, SearchInfo, SearchLimits, TranspositionTable};
+
+struct Session {
+    board: Board,
+    table: Option<TranspositionTable>,
+    search: Option<Search>,
+}
+
+struct Search {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<TranspositionTable>,
+}
+
+fn main() {
+    let mut session = Session {
+        board: Board::new(),
+        table: Some(new_table()),
+        search: None,
+    };
+
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some("isready") => println!("readyok"),
+            Some("newgame") => {
+                session.stop();
+                session.board = Board::new();
+                session.table = Some(new_table());
+            }
+            Some("position") => {
+                session.stop();
+
+                match parse_position(words) {
+                    Ok(board) => session.board = board,
+                    Err(message) => println!("info string {message}"),
+                }
+            }
+            Some("go") => {
+                session.stop();
+
+                match parse_limits(words) {
+                    Ok(limits) => session.go(limits),
+                    Err(message) => println!("info string {message}"),
+                }
+            }
+            Some("stop") => session.stop(),
+            Some("quit") => break,
+            Some(command) => println!("info string unknown command {command}"),
+            None => (),
+        }
+    }
+
+    session.stop();
+}
+
+impl Session {
+    fn go(&mut self, limits: SearchLimits) {
+        if self.board.game_over() {
+            println!("info string the game is already over");
+            println!("bestmove none");
+            return;
+        }
+
+        let board = self.board.clone();
+        let mut table = self.table.take().unwrap();
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&stop);
+
+        let handle = thread::spawn(move || {
+            let mv = search(&board, &limits, Some(&flag), &mut table, print_info);
+            println!("bestmove {mv}");
+            table
+        });
+
+        self.search = Some(Search { stop, handle });
+    }
+
+    fn stop(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.stop.store(true, Ordering::Relaxed);
+            self.table = Some(search.handle.join().unwrap());
+        }
+    }
+}
+
+fn new_table() -> TranspositionTable {
+    TranspositionTable::with_memory(DEFAULT_HASH_MB * 1024 * 1024)
+}
+
+fn parse_position<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<Board, String> {
+    let mut board = match words.next() {
+        Some("startpos") => Board::new(),
+        Some("grid") => {
+            let grid = words
+                .by_ref()
+                .take_while(|&word| word != "moves")
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            Board::from_grid(&grid).map_err(|error| format!("invalid grid {grid}: {error}"))?
+        }
+        _ => return Err(String::from("expected position startpos or position grid")),
+    };
+
+    for word in words.skip_while(|&word| word == "moves") {
+        let mv = Move::parse(word).ok_or_else(|| format!("invalid move {word}"))?;
+        board
+            .play_move(mv)
+            .map_err(|error| format!("illegal move {word}: {error}"))?;
+    }
+
+    Ok(board)
+}
+
+fn parse_limits<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<SearchLimits, String> {
+    let mut limits = SearchLimits::default();
+
+    while let Some(word) = words.next() {
+        if word == "infinite" {
+            continue;
+        }
+
+        let value = words
+            .next()
+            .and_then(|value| value.parse::<u64>().ok())
+            .ok_or_else(|| format!("expected a number after {word}"))?;
+
+        match word {
+            "depth" => limits.depth = Some(value as usize),
+            "movetime" => limits.time = Some(Duration::from_millis(value)),
+            _ => return Err(format!("unknown search limit {word}")),
+        }
+    }
+
+    Ok(limits)
+}
+
+fn print_info(info: &SearchInfo) {
+    let score = match info.outcome {
+        Some(Score::Win(distance)) => format!("win {distance}"),
+        Some(Score::Loss(distance)) => format!("loss {distance}"),
+        _ => format!("cp {}", (info.score * 100.0).round()),
+    };
+    let pv = info
+        .pv
+        .iter()
+        .map(Move::to_string)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    println!(
+        "info depth {} score {} nodes {} time {} pv {}",
+        info.depth,
+        score,
+        info.nodes,
+        info.time.as_millis(),
+        pv
+    );
+}
@@ -0,0 +1,302 @@
+use std::fs;
+use std::rc::Rc;
+use std::time::Duration;
+
+use connect_four::book::Book;
+use connect_four::engine::{self, Engine, EngineOptions, SearchLimits};
+use connect_four::rules::{self, Rules};
+use connect_four::{Board, GameState};
+
+const DEFAULT_GAMES: usize = 10;
+
+struct Settings {
+    engines: Vec<String>,
+    games: usize,
+    openings: Vec<Board>,
+    rules: Rules,
+    limits: SearchLimits,
+    options: EngineOptions,
+}
+
+#[derive(Default)]
+struct Record {
+    wins: usize,
+    draws: usize,
+    losses: usize,
+}
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let settings = match parse_settings(&args) {
+        Ok(settings) => settings,
+        Err(message) => {
+            println!("{message}");
+            println!(
+                "Usage: tournament [--engines a,b,...] [--games N] [--openings FILE] \
+                 [--depth N] [--time MS] [--iterations N] [--book FILE] [--size WxH] [--connect N] [--popout]"
+            );
+            return;
+        }
+    };
+
+    let mut results = Vec::new();
+
+    for (i, first) in settings.engines.iter().enumerate() {
+        for second in &settings.engines[i + 1..] {
+            let record = play_match(first, second, &settings);
+            results.push((first.as_str(), second.as_str(), record));
+        }
+    }
+
+    println!();
+    println!(
+        "{:<10} {:<10} {:>5} {:>5} {:>5} {:>7} {:>14}",
+        "Engine", "Opponent", "W", "D", "L", "Score", "Elo"
+    );
+
+    for (first, second, record) in &results {
+        println!(
+            "{:<10} {:<10} {:>5} {:>5} {:>5} {:>6.1}% {:>14}",
+            first,
+            second,
+            record.wins,
+            record.draws,
+            record.losses,
+            record.score() * 100.0,
+            record.elo(),
+        );
+    }
+}
+
+fn play_match(first: &str, second: &str, settings: &Settings) -> Record {
+    let mut first_engine = engine::create(first, &settings.options).unwrap();
+    let mut second_engine = engine::create(second, &settings.options).unwrap();
+    let mut record = Record::default();
+
+    let openings = if settings.openings.is_empty() {
+        vec![Board::with_rules(settings.rules)]
+    } else {
+        settings.openings.clone()
+    };
+
+    for game in 0..settings.games {
+        let opening = &openings[(game / 2) % openings.len()];
+        let first_is_red = game.is_multiple_of(2);
+
+        let result = if first_is_red {
+            play_game(
+                first_engine.as_mut(),
+                second_engine.as_mut(),
+                opening,
+                settings,
+            )
+        } else {
+            play_game(
+                second_engine.as_mut(),
+                first_engine.as_mut(),
+                opening,
+                settings,
+            )
+        };
+
+        match (result, first_is_red) {
+            (GameState::RedWon, true) | (GameState::YellowWon, false) => record.wins += 1,
+            (GameState::RedWon, false) | (GameState::YellowWon, true) => record.losses += 1,
+            _ => record.draws += 1,
+        }
+
+        println!(
+            "{} vs {}: game {}/{}: +{} ={} -{}",
+            first,
+            second,
+            game + 1,
+            settings.games,
+            record.wins,
+            record.draws,
+            record.losses,
+        );
+    }
+
+    record
+}
+
+fn play_game(
+    red: &mut dyn Engine,
+    yellow: &mut dyn Engine,
+    opening: &Board,
+    settings: &Settings,
+) -> GameState {
+    let limits = &settings.limits;
+    let mut board = opening.clone();
+
+    while !board.game_over() {
+        let turn = board.position().current_player();
+        let mv = if turn {
+            red.choose_move(&board, limits)
+        } else {
+            yellow.choose_move(&board, limits)
+        };
+
+        board.play_move(mv).unwrap();
+    }
+
+    board.game_state().clone()
+}
+
+fn parse_settings(args: &[String]) -> Result<Settings, String> {
+    let mut settings = Settings {
+        engines: engine::names()
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+        games: DEFAULT_GAMES,
+        openings: Vec::new(),
+        rules: Rules::default(),
+        limits: SearchLimits::default(),
+        options: EngineOptions::default(),
+    };
+
+    let mut openings_path = None;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--popout" {
+            settings.rules.pop_out = true;
+            continue;
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {arg}"))?;
+
+        match arg.as_str() {
+            "--engines" => {
+                settings.engines = value.split(',').map(|name| name.to_string()).collect();
+            }
+            "--games" => settings.games = parse_number(arg, value)?,
+            "--openings" => openings_path = Some(value),
+            "--size" => {
+                let (width, height) = rules::parse_size(value)
+                    .ok_or_else(|| format!("Invalid board size {value}"))?;
+                settings.rules.width = width;
+                settings.rules.height = height;
+            }
+            "--connect" => settings.rules.win_length = parse_number(arg, value)?,
+            "--depth" => settings.limits.depth = Some(parse_number(arg, value)?),
+            "--time" => {
+                let millis = parse_number(arg, value)?;
+                settings.limits.time = Some(Duration::from_millis(millis as u64));
+            }
+            "--iterations" => settings.limits.iterations = Some(parse_number(arg, value)?),
+            "--book" => {
+                let book =
+                    Book::load(value).map_err(|error| format!("Cannot load {value}: {error}"))?;
+                settings.options.book = Some(Rc::new(book));
+            }
+            _ => return Err(format!("Unknown option {arg}")),
+        }
+    }
+
+    for name in &settings.engines {
+        if !engine::names().contains(&name.as_str()) {
+            return Err(format!(
+                "Unknown engine {}, available engines: {}",
+                name,
+                engine::names().join(", ")
+            ));
+        }
+    }
+
+    if !settings.rules.is_valid() {
+        return Err(format!(
+            "Cannot play connect {} on a {}x{} board",
+            settings.rules.win_length, settings.rules.width, settings.rules.height
+        ));
+    }
+
+    if let Some(path) = openings_path {
+        settings.openings = load_openings(path, settings.rules)?;
+    }
+
+    if settings.engines.len() < 2 {
+        return Err(String::from("A tournament needs at least two engines"));
+    }
+
+    Ok(settings)
+}
+
+fn parse_number(arg: &str, value: &str) -> Result<usize, String> {
+    value
+        .parse::<usize>()
+        .map_err(|_| format!("{arg} must be a non-negative number"))
+}
+
+fn load_openings(path: &str, rules: Rules) -> Result<Vec<Board>, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|error| format!("Cannot read {path}: {error}"))?;
+    let mut openings = Vec::new();
+
+    for (line_number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut board = Board::with_rules(rules);
+
+        if let Err(error) = board.play_moves(line) {
+            return Err(format!(
+                "Invalid opening on line {}: {error}",
+                line_number + 1
+            ));
+        }
+
+        if board.game_over() {
+            return Err(format!("Illegal opening on line {}", line_number + 1));
+        }
+
+        openings.push(board);
+    }
+
+    Ok(openings)
+}
+
+impl Record {
+    fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    fn score(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.5;
+        }
+
+        (self.wins as f64 + 0.5 * self.draws as f64) / self.games() as f64
+    }
+
+    fn elo(&self) -> String {
+        let games = self.games() as f64;
+        let score = self.score();
+
+        if score <= 0.0 || score >= 1.0 {
+            return if score >= 1.0 { "+inf" } else { "-inf" }.to_string();
+        }
+
+        let deviation = ((self.wins as f64 * (1.0 - score).powi(2)
+            + self.draws as f64 * (0.5 - score).powi(2)
+            + self.losses as f64 * score.powi(2))
+            / games)
+            .sqrt();
+        let error = 1.96 * deviation / games.sqrt();
+
+        let low = elo_difference((score - error).max(f64::EPSILON));
+        let high = elo_difference((score + error).min(1.0 - f64::EPSILON));
+
+        format!("{:+.0} ± {:.0}", elo_difference(score), (high - low) / 2.0)
+    }
+}
+
+fn elo_difference(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
@@ -1,169 +1,335 @@
 use std::fmt;
 
 use crate::coin::Coin;
-use crate::game_state::GameState;
+use crate::error::DropError;
+use crate::game_move::Move;
+use crate::game_state::{GameState, WinningLine};
+use crate::position::Position;
+use crate::rules::Rules;
 
-const BOARD_WIDTH: usize = 7;
-const BOARD_HEIGHT: usize = 6;
+const REPETITION_LIMIT: usize = 3;
 
 #[derive(Clone)]
 pub struct Board {
-    board: Vec<Vec<Coin>>,
+    position: Position,
     game_state: GameState,
+    winning_line: Option<WinningLine>,
+    history: Vec<Move>,
+    previous_positions: Vec<Position>,
+    undone: Vec<Move>,
 }
 
 impl Board {
     pub fn new() -> Self {
-        let mut board_vec = Vec::with_capacity(BOARD_HEIGHT);
+        Self::with_rules(Rules::default())
+    }
 
-        for _ in 0..BOARD_HEIGHT {
-            board_vec.push(vec![Coin::Empty; BOARD_WIDTH]);
-        }
+    pub fn with_rules(rules: Rules) -> Self {
+        Self::from_position(Position::with_rules(rules))
+    }
 
-        Self {
-            board: board_vec,
+    pub(crate) fn from_position(position: Position) -> Self {
+        let mut board = Self {
+            position,
             game_state: GameState::OnGoing,
-        }
+            winning_line: None,
+            history: Vec::new(),
+            previous_positions: Vec::new(),
+            undone: Vec::new(),
+        };
+
+        board.update_state();
+        board
+    }
+
+    pub fn rules(&self) -> &Rules {
+        self.position.rules()
+    }
+
+    pub fn width(&self) -> usize {
+        self.position.width()
     }
 
-    pub fn drop(&mut self, col: usize, turn: bool) -> Result<(), ()> {
-        let mut row = 0;
+    pub fn height(&self) -> usize {
+        self.position.height()
+    }
+
+    pub fn drop(&mut self, col: usize) -> Result<(), DropError> {
+        self.check_move(col)?;
 
-        if self.board[row][col] != Coin::Empty {
-            return Err(());
+        if !self.position.can_play(col) {
+            return Err(DropError::ColumnFull(col));
         }
 
-        while row + 1 < BOARD_HEIGHT {
-            if self.board[row + 1][col] == Coin::Empty {
-                row += 1;
-            } else {
-                break;
-            }
+        self.undone.clear();
+        self.apply(Move::Drop(col));
+
+        Ok(())
+    }
+
+    pub fn pop(&mut self, col: usize) -> Result<(), DropError> {
+        self.check_move(col)?;
+
+        if !self.position.can_pop(col) {
+            return Err(DropError::CannotPop(col));
         }
 
-        self.board[row][col] = if turn { Coin::Red } else { Coin::Yellow };
-        self.update_state();
+        self.undone.clear();
+        self.apply(Move::Pop(col));
 
         Ok(())
     }
 
+    pub fn play_move(&mut self, mv: Move) -> Result<(), DropError> {
+        match mv {
+            Move::Drop(col) => self.drop(col),
+            Move::Pop(col) => self.pop(col),
+        }
+    }
+
+    pub fn undo(&mut self) -> Option<Move> {
+        let mv = self.history.pop()?;
+        self.position = self.previous_positions.pop().unwrap();
+        self.game_state = GameState::OnGoing;
+        self.winning_line = None;
+        self.undone.push(mv);
+
+        Some(mv)
+    }
+
+    pub fn redo(&mut self) -> Option<Move> {
+        let mv = self.undone.pop()?;
+        self.apply(mv);
+
+        Some(mv)
+    }
+
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    pub fn start_position(&self) -> &Position {
+        self.previous_positions.first().unwrap_or(&self.position)
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.position.legal_moves()
+    }
+
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
     pub fn get_board(&self) -> Vec<Vec<Coin>> {
-        self.board.clone()
+        let mut board_vec = Vec::with_capacity(self.height());
+
+        for row in 0..self.height() {
+            board_vec.push(
+                (0..self.width())
+                    .map(|col| self.position.cell(row, col))
+                    .collect(),
+            );
+        }
+
+        board_vec
     }
 
     pub fn game_state(&self) -> &GameState {
         &self.game_state
     }
 
+    pub fn winning_line(&self) -> Option<WinningLine> {
+        self.winning_line
+    }
+
     pub fn game_over(&self) -> bool {
         self.game_state != GameState::OnGoing
     }
 
-    fn update_state(&mut self) {
-        for i in &self.board {
-            for j in i.windows(4) {
-                if j[0] == j[1] && j[0] == j[2] && j[0] == j[3] {
-                    match j[0] {
-                        Coin::Empty => (),
-                        Coin::Red => self.game_state = GameState::RedWon,
-                        Coin::Yellow => self.game_state = GameState::YellowWon,
-                    }
-                }
-            }
+    fn check_move(&self, col: usize) -> Result<(), DropError> {
+        if self.game_over() {
+            Err(DropError::GameAlreadyOver)
+        } else if col >= self.width() {
+            Err(DropError::ColumnOutOfRange(col))
+        } else {
+            Ok(())
         }
+    }
 
-        for i in 0..3 {
-            for j in 0..BOARD_WIDTH {
-                let top = &self.board[i][j];
-                let middle_top = &self.board[i + 1][j];
-                let middle_bottom = &self.board[i + 2][j];
-                let bottom = &self.board[i + 3][j];
-
-                if top == middle_top && top == middle_bottom && top == bottom {
-                    match top {
-                        Coin::Empty => (),
-                        Coin::Red => self.game_state = GameState::RedWon,
-                        Coin::Yellow => self.game_state = GameState::YellowWon,
-                    }
-                }
-            }
-        }
+    fn apply(&mut self, mv: Move) {
+        self.history.push(mv);
+        self.previous_positions.push(self.position);
+        self.position.play_move(mv);
 
-        for i in 0..3 {
-            for j in 0..4 {
-                let top_left = &self.board[i][j];
-                let left = &self.board[i + 1][j + 1];
-                let right = &self.board[i + 2][j + 2];
-                let bottom_right = &self.board[i + 3][j + 3];
-
-                if top_left == left && top_left == right && top_left == bottom_right {
-                    match top_left {
-                        Coin::Empty => (),
-                        Coin::Red => self.game_state = GameState::RedWon,
-                        Coin::Yellow => self.game_state = GameState::YellowWon,
-                    }
-                }
-            }
-        }
+        self.update_state();
+    }
 
-        for i in 0..3 {
-            for j in 3..BOARD_WIDTH {
-                let top_left = &self.board[i][j];
-                let left = &self.board[i + 1][j - 1];
-                let right = &self.board[i + 2][j - 2];
-                let bottom_right = &self.board[i + 3][j - 3];
-
-                if top_left == left && top_left == right && top_left == bottom_right {
-                    match top_left {
-                        Coin::Empty => (),
-                        Coin::Red => self.game_state = GameState::RedWon,
-                        Coin::Yellow => self.game_state = GameState::YellowWon,
-                    }
-                }
-            }
-        }
+    fn update_state(&mut self) {
+        self.winning_line = self.position.winning_line();
 
-        if self.is_full() && self.game_state == GameState::OnGoing {
-            self.game_state = GameState::Draw;
+        match self.position.winner() {
+            Some(Coin::Red) => self.game_state = GameState::RedWon,
+            Some(Coin::Yellow) => self.game_state = GameState::YellowWon,
+            _ if self.position.is_blocked() => self.game_state = GameState::Draw,
+            _ => (),
         }
-    }
 
-    fn is_full(&self) -> bool {
-        for i in &self.board[0] {
-            if *i == Coin::Empty {
-                return false;
+        if self.rules().pop_out && self.game_state == GameState::OnGoing {
+            // The key alone would match a colour-swapped position with the other side to move.
+            let current = (self.position.key(), self.position.current_player());
+            let repetitions = self
+                .previous_positions
+                .iter()
+                .filter(|position| (position.key(), position.current_player()) == current)
+                .count();
+
+            if repetitions + 1 >= REPETITION_LIMIT {
+                self.game_state = GameState::Draw;
             }
         }
+    }
+}
 
-        true
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let numbers = "  1   2   3   4   5   6   7\n";
-        let sep_line_top = "┌───┬───┬───┬───┬───┬───┬───┐\n";
-        let sep_line = "├───┼───┼───┼───┼───┼───┼───┤\n";
-        let sep_line_bottom = "└───┴───┴───┴───┴───┴───┴───┘\n";
-
-        let mut board_str = numbers.to_string() + sep_line_top;
-
-        for i in &self.board {
-            for j in i {
-                board_str += match j {
-                    Coin::Empty => "│   ",
-                    Coin::Red => "│ R ",
-                    Coin::Yellow => "│ Y ",
+        let numbers = (1..=self.width())
+            .map(|i| format!("  {i:<2}"))
+            .collect::<String>();
+        let cells = vec!["───"; self.width()];
+        let sep_line_top = format!("┌{}┐\n", cells.join("┬"));
+        let sep_line = format!("├{}┤\n", cells.join("┼"));
+        let sep_line_bottom = format!("└{}┘\n", cells.join("┴"));
+
+        let mut board_str = numbers.trim_end().to_string() + "\n" + &sep_line_top;
+
+        for (row, i) in self.get_board().iter().enumerate() {
+            for (col, j) in i.iter().enumerate() {
+                let highlighted = self
+                    .winning_line()
+                    .is_some_and(|line| line.contains(row, col));
+
+                board_str += match (j, highlighted) {
+                    (Coin::Empty, _) => "│   ",
+                    (Coin::Red, false) => "│ R ",
+                    (Coin::Yellow, false) => "│ Y ",
+                    (Coin::Red, true) => "│[R]",
+                    (Coin::Yellow, true) => "│[Y]",
                 }
             }
 
             board_str += "│\n";
-            board_str += sep_line;
+            board_str += &sep_line;
         }
 
         board_str = board_str[..board_str.len() - sep_line.len()].to_string();
-        board_str += sep_line_bottom;
+        board_str += &sep_line_bottom;
 
         write!(f, "{board_str}")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn popout_board() -> Board {
+        Board::with_rules(Rules {
+            pop_out: true,
+            ..Rules::default()
+        })
+    }
+
+    #[test]
+    fn undo_restores_state_after_a_win() {
+        let mut board = Board::from_moves("121212").unwrap();
+        let before = *board.position();
+        board.drop(0).unwrap();
+
+        assert!(board.game_state() == &GameState::RedWon);
+        assert!(board.winning_line().is_some());
+        assert_eq!(board.undo(), Some(Move::Drop(0)));
+        assert!(board.game_state() == &GameState::OnGoing);
+        assert!(board.winning_line().is_none());
+        assert!(board.position() == &before);
+        assert_eq!(board.history().len(), 6);
+    }
+
+    #[test]
+    fn redo_replays_undone_moves() {
+        let mut board = Board::from_moves("1212121").unwrap();
+        let finished = *board.position();
+
+        board.undo();
+        board.undo();
+        assert_eq!(board.redo(), Some(Move::Drop(1)));
+        assert_eq!(board.redo(), Some(Move::Drop(0)));
+        assert_eq!(board.redo(), None);
+        assert!(board.position() == &finished);
+        assert!(board.game_state() == &GameState::RedWon);
+        assert_eq!(
+            board.history(),
+            Board::from_moves("1212121").unwrap().history()
+        );
+    }
+
+    #[test]
+    fn new_move_clears_redo() {
+        let mut board = Board::from_moves("4455").unwrap();
+
+        board.undo();
+        board.drop(2).unwrap();
+
+        assert_eq!(board.redo(), None);
+        assert_eq!(
+            board.history(),
+            Board::from_moves("4453").unwrap().history()
+        );
+    }
+
+    #[test]
+    fn start_position_of_grid_board() {
+        let grid = "7/7/7/7/3y3/2rr3 y";
+        let mut board = Board::from_grid(grid).unwrap();
+        let start = *board.position();
+
+        assert!(board.start_position() == &start);
+
+        board.play_moves("3526").unwrap();
+
+        assert!(board.start_position() == &start);
+        assert_eq!(
+            Board::from_position(*board.start_position()).to_grid(),
+            grid
+        );
+
+        while board.undo().is_some() {}
+
+        assert!(board.position() == &start);
+    }
+
+    #[test]
+    fn repeated_popout_position_is_a_draw() {
+        let mut board = popout_board();
+
+        board.play_moves("12p1p2").unwrap();
+        assert!(board.game_state() == &GameState::OnGoing);
+
+        board.play_moves("12p1p2").unwrap();
+        assert!(board.game_state() == &GameState::Draw);
+    }
+
+    #[test]
+    fn pop_rejects_opponent_coins() {
+        let mut board = popout_board();
+        board.play_moves("1").unwrap();
+
+        assert_eq!(board.pop(0), Err(DropError::CannotPop(0)));
+        assert_eq!(board.pop(1), Err(DropError::CannotPop(1)));
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+use crate::board::Board;
+use crate::bot::{self, AlphaBeta, Score, SolverTable};
+use crate::engine::{Engine, EngineOptions, SearchLimits};
+use crate::position::{Position, BOARD_WIDTH};
+
+pub const DEFAULT_BOOK_PATH: &str = "book.bin";
+pub const DEFAULT_BOOK_DEPTH: usize = 8;
+
+const MAGIC: &[u8; 4] = b"C4BK";
+const HEADER_SIZE: usize = 8;
+const ENTRY_SIZE: usize = 10;
+const UNKNOWN_VALUE: i8 = i8::MIN;
+
+pub struct Book {
+    entries: Vec<Entry>,
+}
+
+#[derive(Clone, Copy)]
+pub struct BookMove {
+    pub column: usize,
+    pub score: Option<Score>,
+}
+
+#[derive(Clone, Copy)]
+struct Entry {
+    key: u64,
+    column: u8,
+    value: i8,
+}
+
+pub enum Generator {
+    Solver,
+    Search(usize),
+}
+
+impl Book {
+    pub fn load(path: &str) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn lookup(&self, board: &Board) -> Option<BookMove> {
+        let position = board.position();
+
+        if !position.is_standard() {
+            return None;
+        }
+
+        let book_move = match self.find(position.key() as u64) {
+            Some(entry) => entry.book_move(false),
+            None => self.find(position.mirrored().key() as u64)?.book_move(true),
+        };
+
+        position.can_play(book_move.column).then_some(book_move)
+    }
+
+    fn find(&self, key: u64) -> Option<&Entry> {
+        self.entries
+            .binary_search_by_key(&key, |entry| entry.key)
+            .ok()
+            .map(|index| &self.entries[index])
+    }
+
+    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "not an opening book");
+
+        if bytes.len() < HEADER_SIZE || &bytes[..4] != MAGIC {
+            return Err(invalid());
+        }
+
+        let count = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+
+        if bytes.len() != HEADER_SIZE + count * ENTRY_SIZE {
+            return Err(invalid());
+        }
+
+        let entries = bytes[HEADER_SIZE..]
+            .chunks_exact(ENTRY_SIZE)
+            .map(|chunk| Entry {
+                key: u64::from_le_bytes(chunk[..8].try_into().unwrap()),
+                column: chunk[8],
+                value: chunk[9] as i8,
+            })
+            .collect::<Vec<_>>();
+
+        if entries.windows(2).any(|pair| pair[0].key >= pair[1].key)
+            || entries
+                .iter()
+                .any(|entry| entry.column as usize >= BOARD_WIDTH)
+        {
+            return Err(invalid());
+        }
+
+        Ok(Self { entries })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + self.entries.len() * ENTRY_SIZE);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+
+        for entry in &self.entries {
+            bytes.extend_from_slice(&entry.key.to_le_bytes());
+            bytes.push(entry.column);
+            bytes.push(entry.value as u8);
+        }
+
+        bytes
+    }
+}
+
+impl Entry {
+    fn book_move(&self, mirrored: bool) -> BookMove {
+        let column = self.column as usize;
+
+        BookMove {
+            column: if mirrored {
+                BOARD_WIDTH - 1 - column
+            } else {
+                column
+            },
+            score: decode_score(self.value),
+        }
+    }
+}
+
+pub fn generate(
+    plies: usize,
+    generator: &Generator,
+    options: &EngineOptions,
+    mut progress: impl FnMut(usize, usize),
+) -> Book {
+    let mut entries = HashMap::new();
+    let mut frontier = vec![Board::new()];
+    let mut search = AlphaBeta::new(options);
+    let mut solver_table = None;
+
+    for ply in 0..=plies {
+        let mut next_frontier = Vec::new();
+
+        for board in frontier {
+            let (key, mirrored) = canonical_key(board.position());
+
+            if entries.contains_key(&key) {
+                continue;
+            }
+
+            let (column, value) = match generator {
+                Generator::Solver => {
+                    let table = solver_table.get_or_insert_with(SolverTable::new);
+                    let (column, score) = bot::get_solver_move_with_table(&board, table);
+                    (column, encode_score(score))
+                }
+                Generator::Search(depth) => {
+                    let limits = SearchLimits {
+                        depth: Some(*depth),
+                        ..SearchLimits::default()
+                    };
+                    (search.choose_move(&board, &limits).column(), UNKNOWN_VALUE)
+                }
+            };
+
+            let column = if mirrored {
+                BOARD_WIDTH - 1 - column
+            } else {
+                column
+            };
+
+            entries.insert(
+                key,
+                Entry {
+                    key,
+                    column: column as u8,
+                    value,
+                },
+            );
+
+            if entries.len() % 1000 == 0 {
+                progress(entries.len(), ply);
+            }
+
+            if ply == plies {
+                continue;
+            }
+
+            for i in board.legal_moves() {
+                let mut next = board.clone();
+                next.play_move(i).unwrap();
+
+                if !next.game_over() {
+                    next_frontier.push(next);
+                }
+            }
+        }
+
+        frontier = next_frontier;
+    }
+
+    let mut entries = entries.into_values().collect::<Vec<_>>();
+    entries.sort_by_key(|entry| entry.key);
+
+    Book { entries }
+}
+
+fn canonical_key(position: &Position) -> (u64, bool) {
+    let key = position.key() as u64;
+    let mirrored_key = position.mirrored().key() as u64;
+
+    if mirrored_key < key {
+        (mirrored_key, true)
+    } else {
+        (key, false)
+    }
+}
+
+fn encode_score(score: Score) -> i8 {
+    match score {
+        Score::Win(distance) => distance as i8,
+        Score::Loss(distance) => -(distance as i8),
+        Score::Draw => 0,
+    }
+}
+
+fn decode_score(value: i8) -> Option<Score> {
+    match value {
+        UNKNOWN_VALUE => None,
+        0 => Some(Score::Draw),
+        i if i > 0 => Some(Score::Win(i as usize)),
+        i => Some(Score::Loss(i.unsigned_abs() as usize)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book_for(board: &Board, column: u8) -> Book {
+        Book {
+            entries: vec![Entry {
+                key: board.position().key() as u64,
+                column,
+                value: UNKNOWN_VALUE,
+            }],
+        }
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let book = book_for(&Board::from_moves("44").unwrap(), 3);
+        let loaded = Book::from_bytes(&book.to_bytes()).unwrap();
+
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.entries[0].column, 3);
+    }
+
+    #[test]
+    fn rejects_columns_outside_the_board() {
+        let book = book_for(&Board::new(), BOARD_WIDTH as u8);
+
+        assert!(Book::from_bytes(&book.to_bytes()).is_err());
+    }
+
+    #[test]
+    fn skips_illegal_book_moves() {
+        let board = Board::from_moves("111111").unwrap();
+
+        assert!(book_for(&board, 0).lookup(&board).is_none());
+        assert_eq!(book_for(&board, 3).lookup(&board).unwrap().column, 3);
+    }
+}
@@ -1,6 +1,27 @@
-use std::collections::HashMap;
-
+use std::cmp::{Ordering, Reverse};
+use std::fmt;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::time::{Duration, Instant};
+
+use crate::board::Board;
+use crate::book::Book;
 use crate::coin::Coin;
+use crate::engine::{Engine, EngineOptions, SearchLimits, DEFAULT_HASH_MB};
+use crate::game_move::Move;
+use crate::position::{Position, BOARD_HEIGHT, BOARD_WIDTH};
+use crate::transposition::{Bound, Entry, TranspositionTable};
+
+const WIN_SCORE: f64 = 1000.0;
+const DEFAULT_DEPTH: usize = 9;
+const FALLBACK_DEPTH: usize = 13;
+// The solver needs minutes per move before this point, so it falls back to a deep search.
+const SOLVER_MIN_MOVES: usize = 12;
+// The solver's score range and table keys assume the standard board.
+const CELL_COUNT: i32 = (BOARD_WIDTH * BOARD_HEIGHT) as i32;
+const MIN_SCORE: i32 = -CELL_COUNT / 2 + 3;
+const TABLE_SIZE: usize = 8388593;
+const DEADLINE_CHECK_INTERVAL: usize = 4096;
 
 struct ContinuousType {
     diagonal: usize,
@@ -8,6 +29,73 @@ struct ContinuousType {
     vertical: usize,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Score {
+    Win(usize),
+    Loss(usize),
+    Draw,
+}
+
+pub(crate) struct SolverTable {
+    keys: Vec<u32>,
+    values: Vec<u8>,
+}
+
+pub struct AlphaBeta {
+    table: TranspositionTable,
+    book: Option<Rc<Book>>,
+}
+
+pub struct Solver {
+    fallback: AlphaBeta,
+}
+
+pub struct ColumnAnalysis {
+    pub mv: Move,
+    pub score: f64,
+    pub outcome: Option<Score>,
+    pub pv: Vec<Move>,
+}
+
+pub struct Hint {
+    pub mv: Move,
+    pub reason: MoveReason,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MoveReason {
+    Win,
+    Block { opponent: Coin, line: LineKind },
+    DoubleThreat,
+    Threat { row: usize, line: LineKind },
+    AvoidsLosingSquare { opponent: Coin },
+    Heuristic,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LineKind {
+    Horizontal,
+    Vertical,
+    Diagonal,
+}
+
+pub struct SearchInfo {
+    pub depth: usize,
+    pub score: f64,
+    pub outcome: Option<Score>,
+    pub nodes: usize,
+    pub time: Duration,
+    pub pv: Vec<Move>,
+}
+
+struct Search<'a> {
+    table: &'a mut TranspositionTable,
+    deadline: Option<Instant>,
+    stop: Option<&'a AtomicBool>,
+    nodes: usize,
+    stopped: bool,
+}
+
 #[derive(PartialEq, Eq)]
 enum State {
     ComputerWon,
@@ -16,513 +104,1041 @@ enum State {
     None,
 }
 
-pub fn get_computer_move(board: &Vec<Vec<Coin>>) -> usize {
-    get_best_move(board, false)
+pub fn get_computer_move(board: &Board, depth: usize, table: &mut TranspositionTable) -> Move {
+    let mut search = Search::new(table, None, None);
+    let position = board.position();
+    get_best_move(&mut search, position, depth).unwrap().0
+}
+
+pub fn get_computer_move_timed(
+    board: &Board,
+    time: Duration,
+    table: &mut TranspositionTable,
+) -> Move {
+    let limits = SearchLimits {
+        time: Some(time),
+        ..SearchLimits::default()
+    };
+
+    search(board, &limits, None, table, |_| ())
+}
+
+pub fn search(
+    board: &Board,
+    limits: &SearchLimits,
+    stop: Option<&AtomicBool>,
+    table: &mut TranspositionTable,
+    mut report: impl FnMut(&SearchInfo),
+) -> Move {
+    let position = board.position();
+    let start = Instant::now();
+    let mut search = Search::new(table, limits.time.map(|time| start + time), stop);
+    let mut best_move = get_ordered_moves(position)[0];
+    let max_depth = max_depth(position);
+
+    for depth in 1..=limits.depth.unwrap_or(max_depth).min(max_depth) {
+        let Some((i, score, pv)) = get_best_move(&mut search, position, depth) else {
+            break;
+        };
+
+        best_move = i;
+        report(&SearchInfo {
+            depth,
+            score,
+            outcome: search_outcome(position, score),
+            nodes: search.nodes,
+            time: start.elapsed(),
+            pv,
+        });
+    }
+
+    best_move
+}
+
+pub fn get_solver_move(board: &Board) -> usize {
+    get_solver_move_with_score(board).0
+}
+
+pub fn get_solver_move_with_score(board: &Board) -> (usize, Score) {
+    get_solver_move_with_table(board, &mut SolverTable::new())
+}
+
+pub(crate) fn get_solver_move_with_table(board: &Board, table: &mut SolverTable) -> (usize, Score) {
+    let columns = get_ordered_columns(board.position())
+        .into_iter()
+        .filter(|&i| board.position().can_play(i))
+        .collect::<Vec<_>>();
+    let mut board = board.clone();
+
+    columns
+        .into_iter()
+        .map(|i| {
+            board.drop(i).unwrap();
+            let score = solve_with_table(&board, table).unwrap();
+            board.undo();
+            (i, score)
+        })
+        .min_by_key(|&(_, score)| score)
+        .map(|(i, score)| (i, score.before_move()))
+        .unwrap()
+}
+
+pub fn solve(board: &Board) -> Option<Score> {
+    solve_with_table(board, &mut SolverTable::new())
 }
 
-fn get_best_move(board: &Vec<Vec<Coin>>, turn: bool) -> usize {
-    let mut move_evaluations = HashMap::new();
+fn solve_with_table(board: &Board, table: &mut SolverTable) -> Option<Score> {
+    let position = board.position();
+
+    if !position.is_standard() {
+        return None;
+    }
+
+    match who_won(position) {
+        State::PlayerWon | State::ComputerWon => return Some(Score::Loss(0)),
+        State::Draw => return Some(Score::Draw),
+        State::None => (),
+    }
+
+    let score = solve_position(position, table);
+    let moves = position.moves() as i32;
 
-    for i in get_legal_moves(board) {
-        move_evaluations.insert(i, evaluate_for_move(board, i, turn));
+    if score == 0 {
+        return Some(Score::Draw);
     }
 
-    if turn {
-        *move_evaluations
-            .iter()
-            .max_by(|x, y| x.1.total_cmp(y.1))
-            .unwrap()
-            .0
+    let parity = if score > 0 {
+        moves % 2
     } else {
-        *move_evaluations
-            .iter()
-            .min_by(|x, y| x.1.total_cmp(y.1))
-            .unwrap()
-            .0
+        (moves + 1) % 2
+    };
+    let mut winning_move = CELL_COUNT + 1 - 2 * score.abs();
+
+    if winning_move % 2 != parity {
+        winning_move -= 1;
     }
+
+    let distance = (winning_move - moves + 1) as usize;
+
+    Some(if score > 0 {
+        Score::Win(distance)
+    } else {
+        Score::Loss(distance)
+    })
 }
 
-fn evaluate_for_move(board: &Vec<Vec<Coin>>, after_move: usize, turn: bool) -> f64 {
-    let mut evaluation = 0.0;
-    let mut board_copy = board.clone();
-    let (comp_choice_row, comp_choice_col) = drop(&mut board_copy, after_move, turn);
+fn solve_position(position: &Position, table: &mut SolverTable) -> i32 {
+    let moves = position.moves() as i32;
 
-    if who_won(&board_copy) == State::ComputerWon {
-        return -f64::INFINITY;
+    if position.can_win_next() {
+        return (CELL_COUNT + 1 - moves) / 2;
     }
 
-    for i in get_legal_moves(board) {
-        let (placed_row, placed_col) = drop(&mut board_copy, i as usize, true);
+    let mut min = -(CELL_COUNT - moves) / 2;
+    let mut max = (CELL_COUNT + 1 - moves) / 2;
 
-        if who_won(&board_copy) == State::PlayerWon {
-            return f64::INFINITY;
+    while min < max {
+        let mut med = min + (max - min) / 2;
+
+        if med <= 0 && min / 2 < med {
+            med = min / 2;
+        } else if med >= 0 && max / 2 > med {
+            med = max / 2;
         }
 
-        board_copy[placed_row][placed_col] = Coin::Empty;
+        let score = solver_negamax(position, med, med + 1, table);
+
+        if score <= med {
+            max = score;
+        } else {
+            min = score;
+        }
     }
 
-    let two_count = twos_count(&board_copy, comp_choice_row, comp_choice_col);
-    let three_count = threes_count(&board_copy, comp_choice_row, comp_choice_col);
+    min
+}
 
-    evaluation += -0.6 * three_count.diagonal as f64;
-    evaluation += -0.4 * three_count.horizontal as f64;
-    evaluation += -0.2 * three_count.vertical as f64;
+fn solver_negamax(
+    position: &Position,
+    mut alpha: i32,
+    mut beta: i32,
+    table: &mut SolverTable,
+) -> i32 {
+    let moves = position.moves() as i32;
+    let next_moves = position.possible_non_losing_moves();
+
+    if next_moves == 0 {
+        return -(CELL_COUNT - moves) / 2;
+    }
 
-    evaluation += -0.3 * two_count.diagonal as f64;
-    evaluation += -0.2 * two_count.horizontal as f64;
-    evaluation += -0.1 * two_count.vertical as f64;
+    if moves >= CELL_COUNT - 2 {
+        return 0;
+    }
 
-    get_neighboring_cells(&board_copy, comp_choice_row, comp_choice_col)
-        .iter()
-        .for_each(|x| {
-            evaluation += match x.0 {
-                Coin::Empty => 0.0,
-                Coin::Red => 0.1,
-                Coin::Yellow => -0.1,
+    let min = -(CELL_COUNT - 2 - moves) / 2;
+
+    if alpha < min {
+        alpha = min;
+
+        if alpha >= beta {
+            return alpha;
+        }
+    }
+
+    let max = match table.get(position.key()) {
+        Some(value) => value as i32 + MIN_SCORE - 1,
+        None => (CELL_COUNT - 1 - moves) / 2,
+    };
+
+    if beta > max {
+        beta = max;
+
+        if alpha >= beta {
+            return beta;
+        }
+    }
+
+    let mut candidates = get_ordered_columns(position)
+        .into_iter()
+        .map(|i| next_moves & position.column_mask(i))
+        .filter(|&placed| placed != 0)
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|&placed| Reverse(position.move_score(placed)));
+
+    for placed in candidates {
+        let mut next = *position;
+        next.play(placed);
+        let score = -solver_negamax(&next, -beta, -alpha, table);
+
+        if score >= beta {
+            return score;
+        }
+
+        alpha = alpha.max(score);
+    }
+
+    table.put(position.key(), (alpha - MIN_SCORE + 1) as u8);
+    alpha
+}
+
+pub fn analyze(board: &Board) -> Vec<ColumnAnalysis> {
+    if board.game_over() {
+        return Vec::new();
+    }
+
+    let position = board.position();
+    let depth = DEFAULT_DEPTH.min(max_depth(position));
+    let mut table = TranspositionTable::with_memory(DEFAULT_HASH_MB * 1024 * 1024);
+    let mut search = Search::new(&mut table, None, None);
+    let mut evaluations = Vec::new();
+
+    for depth in 1..=depth {
+        evaluations = evaluate_moves(&mut search, position, depth).unwrap();
+    }
+
+    let mut solver_table = can_solve(board).then(SolverTable::new);
+    let exhaustive = !position.rules().pop_out && depth == max_depth(position);
+
+    evaluations
+        .into_iter()
+        .map(|(mv, score, pv)| {
+            let outcome = if let Some(table) = solver_table.as_mut() {
+                let mut next = board.clone();
+                next.play_move(mv).unwrap();
+                solve_with_table(&next, table).map(Score::before_move)
+            } else {
+                search_outcome(position, score).or(exhaustive.then_some(Score::Draw))
             };
-        });
 
-    evaluation
+            ColumnAnalysis {
+                mv,
+                score,
+                outcome,
+                pv,
+            }
+        })
+        .collect()
 }
 
-fn twos_count(board: &Vec<Vec<Coin>>, row: usize, col: usize) -> ContinuousType {
-    let coin_type = &board[row][col];
-    let mut types = ContinuousType {
-        diagonal: 0,
-        horizontal: 0,
-        vertical: 0,
+pub fn best_analysis(analysis: &[ColumnAnalysis]) -> Option<&ColumnAnalysis> {
+    let rank = |column: &ColumnAnalysis| (column.outcome.unwrap_or(Score::Draw), column.score);
+
+    analysis.iter().reduce(|best, column| {
+        let (outcome, score) = rank(column);
+        let (best_outcome, best_score) = rank(best);
+
+        if outcome
+            .cmp(&best_outcome)
+            .then(score.total_cmp(&best_score))
+            == Ordering::Greater
+        {
+            column
+        } else {
+            best
+        }
+    })
+}
+
+pub fn hint(board: &Board) -> Option<Hint> {
+    let analysis = analyze(board);
+    let mv = best_analysis(&analysis)?.mv;
+
+    Some(Hint {
+        mv,
+        reason: move_reason(board, mv),
+    })
+}
+
+pub fn move_reason(board: &Board, mv: Move) -> MoveReason {
+    let position = board.position();
+    let red = position.current_player();
+    let (player, opponent) = if red {
+        (Coin::Red, Coin::Yellow)
+    } else {
+        (Coin::Yellow, Coin::Red)
     };
+    let mut next = *position;
+    let placed = make_move(&mut next, mv);
 
-    let top_left = get_relative_cell(board, row, col, -1, -1).unwrap_or(&Coin::Empty);
-    let top_right = get_relative_cell(board, row, col, -1, 1).unwrap_or(&Coin::Empty);
-    let left = get_relative_cell(board, row, col, 0, -1).unwrap_or(&Coin::Empty);
-    let right = get_relative_cell(board, row, col, 0, 1).unwrap_or(&Coin::Empty);
-    let bottom = get_relative_cell(board, row, col, 1, 0).unwrap_or(&Coin::Empty);
-    let bottom_left = get_relative_cell(board, row, col, 1, -1).unwrap_or(&Coin::Empty);
-    let bottom_right = get_relative_cell(board, row, col, 1, 1).unwrap_or(&Coin::Empty);
-
-    if top_left == coin_type {
-        let top_left_2 = get_relative_cell(board, row, col, -2, -2);
-        let top_left_3 = get_relative_cell(board, row, col, -3, -3);
-        let bottom_right_1 = get_relative_cell(board, row, col, 1, 1);
-        let bottom_right_2 = get_relative_cell(board, row, col, 2, 2);
-
-        if let Some(i) = top_left_2 {
-            if i.equals_or_empty(coin_type) {
-                if let Some(j) = top_left_3 {
-                    if j.equals_or_empty(coin_type) {
-                        types.diagonal += 1;
-                    }
-                }
-
-                if let Some(j) = bottom_right_1 {
-                    if j.equals_or_empty(coin_type) {
-                        types.diagonal += 1;
-                    }
-                }
-            }
-        } else if let (Some(i), Some(j)) = (bottom_right_1, bottom_right_2) {
-            if i.equals_or_empty(coin_type) && j.equals_or_empty(coin_type) {
-                types.diagonal += 1;
-            }
+    if next.winner() == Some(player) {
+        return MoveReason::Win;
+    }
+
+    if let Some((row, col)) = placed {
+        if let Some(direction) = position.line_through(row, col, opponent) {
+            return MoveReason::Block {
+                opponent,
+                line: LineKind::from_direction(direction),
+            };
         }
     }
 
-    if top_right == coin_type {
-        let top_right_2 = get_relative_cell(board, row, col, -2, 2);
-        let top_right_3 = get_relative_cell(board, row, col, -3, 3);
-        let bottom_left_1 = get_relative_cell(board, row, col, 1, -1);
-        let bottom_left_2 = get_relative_cell(board, row, col, 2, -2);
+    if next.winning_moves(red).count_ones() >= 2 {
+        return MoveReason::DoubleThreat;
+    }
 
-        if let Some(i) = top_right_2 {
-            if i.equals_or_empty(coin_type) {
-                if let Some(j) = top_right_3 {
-                    if j.equals_or_empty(coin_type) {
-                        types.diagonal += 1;
-                    }
-                }
+    let threats = position.threat_cells(red);
+    let new_threat = next
+        .threat_cells(red)
+        .into_iter()
+        .filter(|cell| !threats.contains(cell))
+        .max_by_key(|&(row, _)| row);
+
+    if let Some((row, col)) = new_threat {
+        if let Some(direction) = next.line_through(row, col, player) {
+            return MoveReason::Threat {
+                row: position.height() - row,
+                line: LineKind::from_direction(direction),
+            };
+        }
+    }
 
-                if let Some(j) = bottom_left_1 {
-                    if j.equals_or_empty(coin_type) {
-                        types.diagonal += 1;
-                    }
-                }
-            }
-        } else if let (Some(i), Some(j)) = (bottom_left_1, bottom_left_2) {
-            if i.equals_or_empty(coin_type) && j.equals_or_empty(coin_type) {
-                types.diagonal += 1;
-            }
+    let opponent_threats = position.threat_cells(!red);
+    let gives_square = |col: usize| {
+        let mut next = *position;
+        let (row, col) = next.drop(col, red);
+        row > 0 && opponent_threats.contains(&(row - 1, col))
+    };
+    let avoided = (0..position.width())
+        .filter(|&col| position.can_play(col) && Move::Drop(col) != mv)
+        .any(&gives_square);
+
+    if avoided && !matches!(mv, Move::Drop(col) if gives_square(col)) {
+        return MoveReason::AvoidsLosingSquare { opponent };
+    }
+
+    MoveReason::Heuristic
+}
+
+fn get_best_move(
+    search: &mut Search,
+    position: &Position,
+    depth: usize,
+) -> Option<(Move, f64, Vec<Move>)> {
+    let mut best = None;
+    let mut alpha = -f64::INFINITY;
+
+    for i in get_ordered_moves(position) {
+        let mut next = *position;
+        let last_move = make_move(&mut next, i);
+        let mut line = Vec::new();
+        let score = -negamax(
+            search,
+            &next,
+            last_move,
+            depth.max(1) - 1,
+            -f64::INFINITY,
+            -alpha,
+            &mut line,
+        );
+
+        if search.stopped {
+            return None;
+        }
+
+        if best.is_none() || score > alpha {
+            line.insert(0, i);
+            best = Some((i, score, line));
+        }
+
+        alpha = alpha.max(score);
+    }
+
+    best
+}
+
+fn evaluate_moves(
+    search: &mut Search,
+    position: &Position,
+    depth: usize,
+) -> Option<Vec<(Move, f64, Vec<Move>)>> {
+    let mut evaluations = Vec::new();
+
+    for i in get_ordered_moves(position) {
+        let mut next = *position;
+        let last_move = make_move(&mut next, i);
+        let mut line = Vec::new();
+        let score = -negamax(
+            search,
+            &next,
+            last_move,
+            depth.max(1) - 1,
+            -f64::INFINITY,
+            f64::INFINITY,
+            &mut line,
+        );
+
+        if search.stopped {
+            return None;
         }
+
+        line.insert(0, i);
+        evaluations.push((i, score, line));
     }
 
-    if bottom_left == coin_type {
-        let bottom_left_2 = get_relative_cell(board, row, col, 2, -2);
-        let bottom_left_3 = get_relative_cell(board, row, col, 3, -3);
-        let top_right_1 = get_relative_cell(board, row, col, -1, 1);
-        let top_right_2 = get_relative_cell(board, row, col, -2, 2);
+    Some(evaluations)
+}
 
-        if let Some(i) = bottom_left_2 {
-            if i.equals_or_empty(coin_type) {
-                if let Some(j) = bottom_left_3 {
-                    if j.equals_or_empty(coin_type) {
-                        types.diagonal += 1;
-                    }
-                }
+fn negamax(
+    search: &mut Search,
+    position: &Position,
+    last_move: Option<(usize, usize)>,
+    depth: usize,
+    mut alpha: f64,
+    beta: f64,
+    pv: &mut Vec<Move>,
+) -> f64 {
+    pv.clear();
+
+    if search.should_stop() {
+        return 0.0;
+    }
 
-                if let Some(j) = top_right_1 {
-                    if j.equals_or_empty(coin_type) {
-                        types.diagonal += 1;
-                    }
-                }
-            }
-        } else if let (Some(i), Some(j)) = (top_right_1, top_right_2) {
-            if i.equals_or_empty(coin_type) && j.equals_or_empty(coin_type) {
-                types.diagonal += 1;
-            }
+    if let Some(winner) = position.winner() {
+        let score = WIN_SCORE - position.moves() as f64;
+
+        return if (winner == Coin::Red) == position.current_player() {
+            score
+        } else {
+            -score
+        };
+    }
+
+    if position.is_blocked() {
+        return 0.0;
+    }
+
+    if depth == 0 {
+        return match last_move {
+            Some((row, col)) => -evaluate_for_move(position, row, col),
+            None => 0.0,
+        };
+    }
+
+    let key = position.key();
+    let original_alpha = alpha;
+    let mut moves = get_ordered_moves(position);
+
+    if let Some(entry) = search.table.get(key) {
+        // Scores inside the window are searched again so the principal variation stays whole.
+        let cutoff = match entry.bound {
+            Bound::Exact => entry.score <= alpha || entry.score >= beta,
+            Bound::Lower => entry.score >= beta,
+            Bound::Upper => entry.score <= alpha,
+        };
+
+        if entry.depth >= depth && cutoff {
+            return entry.score;
+        }
+
+        if let Some(index) = moves.iter().position(|&i| i == entry.best_move) {
+            moves[..=index].rotate_right(1);
         }
     }
 
-    if bottom_right == coin_type {
-        let bottom_right_2 = get_relative_cell(board, row, col, 2, 2);
-        let bottom_right_3 = get_relative_cell(board, row, col, 3, 3);
-        let top_left_1 = get_relative_cell(board, row, col, -1, -1);
-        let top_left_2 = get_relative_cell(board, row, col, -2, -2);
+    let mut best = -f64::INFINITY;
+    let mut best_move = moves[0];
+
+    for i in moves {
+        let mut next = *position;
+        let last_move = make_move(&mut next, i);
+        let mut line = Vec::new();
+        let score = -negamax(
+            search,
+            &next,
+            last_move,
+            depth - 1,
+            -beta,
+            -alpha,
+            &mut line,
+        );
+
+        if score > best {
+            best = score;
+            best_move = i;
+            pv.clear();
+            pv.push(i);
+            pv.append(&mut line);
+        }
 
-        if let Some(i) = bottom_right_2 {
-            if i.equals_or_empty(coin_type) {
-                if let Some(j) = bottom_right_3 {
-                    if j.equals_or_empty(coin_type) {
-                        types.diagonal += 1;
-                    }
-                }
+        alpha = alpha.max(score);
 
-                if let Some(j) = top_left_1 {
-                    if j.equals_or_empty(coin_type) {
-                        types.diagonal += 1;
-                    }
-                }
-            }
-        } else if let (Some(i), Some(j)) = (top_left_1, top_left_2) {
-            if i.equals_or_empty(coin_type) && j.equals_or_empty(coin_type) {
-                types.diagonal += 1;
-            }
+        if alpha >= beta {
+            break;
         }
     }
 
-    if left == coin_type {
-        let left_2 = get_relative_cell(board, row, col, 0, -2);
-        let left_3 = get_relative_cell(board, row, col, 0, -3);
-        let right_1 = get_relative_cell(board, row, col, 0, 1);
-        let right_2 = get_relative_cell(board, row, col, 0, 2);
+    if search.stopped {
+        return best;
+    }
+
+    let bound = if best <= original_alpha {
+        Bound::Upper
+    } else if best >= beta {
+        Bound::Lower
+    } else {
+        Bound::Exact
+    };
 
-        if let Some(i) = left_2 {
-            if i.equals_or_empty(coin_type) {
-                if let Some(j) = left_3 {
-                    if j.equals_or_empty(coin_type) {
-                        types.horizontal += 1;
-                    }
-                }
+    search.table.put(Entry {
+        key,
+        depth,
+        bound,
+        score: best,
+        best_move,
+    });
 
-                if let Some(j) = right_1 {
-                    if j.equals_or_empty(coin_type) {
-                        types.horizontal += 1;
-                    }
-                }
-            }
-        } else if let (Some(i), Some(j)) = (right_1, right_2) {
-            if i.equals_or_empty(coin_type) && j.equals_or_empty(coin_type) {
-                types.horizontal += 1;
+    best
+}
+
+impl AlphaBeta {
+    pub fn new(options: &EngineOptions) -> Self {
+        Self {
+            table: TranspositionTable::with_memory(options.hash_mb * 1024 * 1024),
+            book: options.book.clone(),
+        }
+    }
+}
+
+impl Engine for AlphaBeta {
+    fn name(&self) -> &'static str {
+        "bot"
+    }
+
+    fn choose_move(&mut self, board: &Board, limits: &SearchLimits) -> Move {
+        if let Some(book_move) = self.book.as_ref().and_then(|book| book.lookup(board)) {
+            return Move::Drop(book_move.column);
+        }
+
+        match limits.time {
+            Some(time) => get_computer_move_timed(board, time, &mut self.table),
+            None => {
+                let depth = limits.depth.unwrap_or(DEFAULT_DEPTH);
+                get_computer_move(board, depth, &mut self.table)
             }
         }
     }
 
-    if right == coin_type {
-        let right_2 = get_relative_cell(board, row, col, 0, 2);
-        let right_3 = get_relative_cell(board, row, col, 0, 3);
-        let left_1 = get_relative_cell(board, row, col, 0, -1);
-        let left_2 = get_relative_cell(board, row, col, 0, -2);
+    fn analyze(&mut self, board: &Board, _limits: &SearchLimits) -> Option<Score> {
+        self.book
+            .as_ref()
+            .and_then(|book| book.lookup(board))
+            .and_then(|book_move| book_move.score)
+    }
+}
+
+impl Solver {
+    pub fn new(options: &EngineOptions) -> Self {
+        Self {
+            fallback: AlphaBeta::new(options),
+        }
+    }
+}
+
+impl Engine for Solver {
+    fn name(&self) -> &'static str {
+        "solver"
+    }
+
+    fn choose_move(&mut self, board: &Board, limits: &SearchLimits) -> Move {
+        if can_solve(board) {
+            return Move::Drop(get_solver_move(board));
+        }
+
+        // Larger boards branch too much for a deep untimed search.
+        let depth = if board.position().is_standard() {
+            FALLBACK_DEPTH
+        } else {
+            DEFAULT_DEPTH
+        };
+        let limits = SearchLimits {
+            depth: Some(limits.depth.unwrap_or(depth)),
+            ..*limits
+        };
+
+        self.fallback.choose_move(board, &limits)
+    }
+
+    fn analyze(&mut self, board: &Board, limits: &SearchLimits) -> Option<Score> {
+        if !can_solve(board) {
+            return self.fallback.analyze(board, limits);
+        }
+
+        solve(board)
+    }
+}
+
+fn can_solve(board: &Board) -> bool {
+    let position = board.position();
+    position.is_standard() && position.moves() >= SOLVER_MIN_MOVES
+}
+
+impl<'a> Search<'a> {
+    fn new(
+        table: &'a mut TranspositionTable,
+        deadline: Option<Instant>,
+        stop: Option<&'a AtomicBool>,
+    ) -> Self {
+        Self {
+            table,
+            deadline,
+            stop,
+            nodes: 0,
+            stopped: false,
+        }
+    }
 
-        if let Some(i) = right_2 {
-            if i.equals_or_empty(coin_type) {
-                if let Some(j) = right_3 {
-                    if j.equals_or_empty(coin_type) {
-                        types.horizontal += 1;
-                    }
-                }
+    fn should_stop(&mut self) -> bool {
+        self.nodes += 1;
 
-                if let Some(j) = left_1 {
-                    if j.equals_or_empty(coin_type) {
-                        types.horizontal += 1;
-                    }
-                }
+        if self.nodes.is_multiple_of(DEADLINE_CHECK_INTERVAL) {
+            if let Some(deadline) = self.deadline {
+                self.stopped |= Instant::now() >= deadline;
             }
-        } else if let (Some(i), Some(j)) = (left_1, left_2) {
-            if i.equals_or_empty(coin_type) && j.equals_or_empty(coin_type) {
-                types.horizontal += 1;
+
+            if let Some(stop) = self.stop {
+                self.stopped |= stop.load(AtomicOrdering::Relaxed);
             }
         }
+
+        self.stopped
     }
+}
 
-    if bottom == coin_type {
-        if row > 2 {
-            types.vertical += 1;
+impl Score {
+    pub fn before_move(self) -> Score {
+        match self {
+            Score::Win(distance) => Score::Loss(distance + 1),
+            Score::Loss(distance) => Score::Win(distance + 1),
+            Score::Draw => Score::Draw,
         }
     }
 
-    types
+    fn rank(&self) -> i32 {
+        match self {
+            Score::Win(distance) => CELL_COUNT + 1 - *distance as i32,
+            Score::Loss(distance) => *distance as i32 - CELL_COUNT - 1,
+            Score::Draw => 0,
+        }
+    }
 }
 
-fn threes_count(board: &Vec<Vec<Coin>>, row: usize, col: usize) -> ContinuousType {
-    let coin_type = &board[row][col];
-    let mut types = ContinuousType {
-        diagonal: 0,
-        horizontal: 0,
-        vertical: 0,
-    };
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
 
-    let top_left_1 = get_relative_cell(board, row, col, -1, -1).unwrap_or(&Coin::Empty);
-    let top_left_2 = get_relative_cell(board, row, col, -2, -2).unwrap_or(&Coin::Empty);
-    let top_right_1 = get_relative_cell(board, row, col, -1, 1).unwrap_or(&Coin::Empty);
-    let top_right_2 = get_relative_cell(board, row, col, -1, 2).unwrap_or(&Coin::Empty);
-    let left_1 = get_relative_cell(board, row, col, 0, -1).unwrap_or(&Coin::Empty);
-    let left_2 = get_relative_cell(board, row, col, 0, -2).unwrap_or(&Coin::Empty);
-    let right_1 = get_relative_cell(board, row, col, 0, 1).unwrap_or(&Coin::Empty);
-    let bottom_1 = get_relative_cell(board, row, col, 1, 0).unwrap_or(&Coin::Empty);
-    let bottom_2 = get_relative_cell(board, row, col, 2, 0).unwrap_or(&Coin::Empty);
-    let bottom_left_1 = get_relative_cell(board, row, col, 1, -1).unwrap_or(&Coin::Empty);
-    let bottom_right_1 = get_relative_cell(board, row, col, 1, 1).unwrap_or(&Coin::Empty);
-
-    let right_2 = get_relative_cell(board, row, col, 0, 2);
-    let bottom_left_2 = get_relative_cell(board, row, col, 2, -2);
-    let bottom_right_2 = get_relative_cell(board, row, col, 1, 2);
-
-    if top_left_1 == coin_type && top_left_2 == coin_type {
-        let top_left_3 = get_relative_cell(board, row, col, -3, -3);
-
-        if let Some(i) = top_left_3 {
-            if i.equals_or_empty(coin_type) {
-                types.diagonal += 1;
-            }
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Score::Win(distance) => write!(f, "win in {distance}"),
+            Score::Loss(distance) => write!(f, "loss in {distance}"),
+            Score::Draw => write!(f, "draw"),
         }
     }
+}
 
-    if top_left_1 == coin_type && bottom_right_1 == coin_type {
-        if let Some(i) = bottom_right_2 {
-            if i.equals_or_empty(coin_type) {
-                types.diagonal += 1;
+impl MoveReason {
+    pub fn describe(&self, addressed: bool) -> String {
+        let name = |coin: &Coin| match (addressed, coin) {
+            (true, _) => "you",
+            (false, Coin::Red) => "Red",
+            (false, _) => "Yellow",
+        };
+        let possessive = |coin: &Coin| match (addressed, coin) {
+            (true, _) => "your",
+            (false, Coin::Red) => "Red's",
+            (false, _) => "Yellow's",
+        };
+
+        match self {
+            MoveReason::Win => String::from("winning the game"),
+            MoveReason::Block { opponent, line } => {
+                format!("blocking {} {}", possessive(opponent), line)
+            }
+            MoveReason::DoubleThreat => String::from("creating a double threat"),
+            MoveReason::Threat { row, line } => {
+                format!("setting up a {line} threat on row {row}")
+            }
+            MoveReason::AvoidsLosingSquare { opponent } => {
+                format!("avoiding giving {} a winning square above", name(opponent))
             }
+            MoveReason::Heuristic => String::from("preferred by evaluation"),
         }
     }
+}
 
-    if top_right_1 == coin_type && top_right_2 == coin_type {
-        let top_right_3 = get_relative_cell(board, row, col, -3, 3);
+impl fmt::Display for MoveReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.describe(false))
+    }
+}
 
-        if let Some(i) = top_right_3 {
-            if i.equals_or_empty(coin_type) {
-                types.diagonal += 1;
-            }
+impl LineKind {
+    fn from_direction(direction: (isize, isize)) -> Self {
+        match direction {
+            (0, _) => LineKind::Horizontal,
+            (_, 0) => LineKind::Vertical,
+            _ => LineKind::Diagonal,
         }
     }
+}
 
-    if top_right_1 == coin_type && bottom_left_1 == coin_type {
-        if let Some(i) = bottom_left_2 {
-            if i.equals_or_empty(coin_type) {
-                types.diagonal += 1;
-            }
+impl fmt::Display for LineKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LineKind::Horizontal => "row",
+            LineKind::Vertical => "column",
+            LineKind::Diagonal => "diagonal",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+impl SolverTable {
+    pub(crate) fn new() -> Self {
+        Self {
+            keys: vec![0; TABLE_SIZE],
+            values: vec![0; TABLE_SIZE],
         }
     }
 
-    if left_1 == coin_type && left_2 == coin_type {
-        let left_3 = get_relative_cell(board, row, col, 0, -3);
+    fn get(&self, key: u128) -> Option<u8> {
+        let index = (key % TABLE_SIZE as u128) as usize;
 
-        if let Some(i) = left_3 {
-            if i.equals_or_empty(coin_type) {
-                types.horizontal += 1;
-            }
+        if self.values[index] != 0 && self.keys[index] == key as u32 {
+            Some(self.values[index])
+        } else {
+            None
         }
     }
 
-    if left_1 == coin_type && right_1 == coin_type {
-        if let Some(i) = right_2 {
-            if i.equals_or_empty(coin_type) {
-                types.horizontal += 1;
+    fn put(&mut self, key: u128, value: u8) {
+        let index = (key % TABLE_SIZE as u128) as usize;
+        self.keys[index] = key as u32;
+        self.values[index] = value;
+    }
+}
+
+fn evaluate_for_move(position: &Position, row: usize, col: usize) -> f64 {
+    let mut evaluation = 0.0;
+    let coin_type = position.cell(row, col);
+    let win_length = position.win_length();
+
+    for length in 2..win_length {
+        let weight = (length - 1) as f64 / (win_length - 2) as f64;
+        let count = lines_count(position, row, col, length);
+
+        evaluation += weight * 0.6 * count.diagonal as f64;
+        evaluation += weight * 0.4 * count.horizontal as f64;
+        evaluation += weight * 0.2 * count.vertical as f64;
+    }
+
+    get_neighboring_cells(position, row, col)
+        .iter()
+        .for_each(|x| {
+            if x.0 != Coin::Empty {
+                evaluation += if x.0 == coin_type { 0.1 } else { -0.1 };
             }
+        });
+
+    evaluation
+}
+
+fn lines_count(position: &Position, row: usize, col: usize, length: usize) -> ContinuousType {
+    let mut types = ContinuousType {
+        diagonal: 0,
+        horizontal: 0,
+        vertical: 0,
+    };
+
+    for direction in [(1, 1), (1, -1), (0, 1), (1, 0)] {
+        if !is_open_line(position, row, col, direction, length) {
+            continue;
         }
-    }
 
-    if bottom_1 == coin_type && bottom_2 == coin_type {
-        types.vertical += 1;
+        match direction {
+            (0, _) => types.horizontal += 1,
+            (_, 0) => types.vertical += 1,
+            _ => types.diagonal += 1,
+        }
     }
 
     types
 }
 
-fn drop(board: &mut Vec<Vec<Coin>>, col: usize, turn: bool) -> (usize, usize) {
-    let mut row = 0;
+fn is_open_line(
+    position: &Position,
+    row: usize,
+    col: usize,
+    (row_shift, col_shift): (isize, isize),
+    length: usize,
+) -> bool {
+    let coin_type = position.cell(row, col);
+    let same = |coin: Coin| coin == coin_type;
+    let open = |coin: Coin| coin.equals_or_empty(&coin_type);
+
+    let line = 1
+        + count_in_direction(position, row, col, (row_shift, col_shift), same)
+        + count_in_direction(position, row, col, (-row_shift, -col_shift), same);
+
+    if line != length {
+        return false;
+    }
 
-    while row + 1 < 6 {
-        if board[row + 1][col] == Coin::Empty {
-            row += 1;
-        } else {
+    let space = 1
+        + count_in_direction(position, row, col, (row_shift, col_shift), open)
+        + count_in_direction(position, row, col, (-row_shift, -col_shift), open);
+
+    space >= position.win_length()
+}
+
+fn count_in_direction(
+    position: &Position,
+    row: usize,
+    col: usize,
+    (row_shift, col_shift): (isize, isize),
+    matches: impl Fn(Coin) -> bool,
+) -> usize {
+    let mut count = 0;
+
+    while let Some(coin) = get_relative_cell(
+        position,
+        row,
+        col,
+        row_shift * (count + 1),
+        col_shift * (count + 1),
+    ) {
+        if !matches(coin) {
             break;
         }
+
+        count += 1;
     }
 
-    board[row][col] = if turn { Coin::Red } else { Coin::Yellow };
-    (row, col)
+    count as usize
 }
 
 fn get_relative_cell(
-    board: &Vec<Vec<Coin>>,
+    position: &Position,
     row: usize,
     col: usize,
     row_shift: isize,
     col_shift: isize,
-) -> Option<&Coin> {
+) -> Option<Coin> {
     let new_row = (row as isize) + row_shift;
     let new_col = (col as isize) + col_shift;
 
-    if new_row < 0 || new_row > 5 || new_col < 0 || new_col > 6 {
+    if !(0..position.height() as isize).contains(&new_row)
+        || !(0..position.width() as isize).contains(&new_col)
+    {
         None
     } else {
-        Some(&board[new_row as usize][new_col as usize])
+        Some(position.cell(new_row as usize, new_col as usize))
     }
 }
 
-fn get_neighboring_cells(
-    board: &Vec<Vec<Coin>>,
-    row: usize,
-    col: usize,
-) -> Vec<(&Coin, usize, usize)> {
+fn get_neighboring_cells(position: &Position, row: usize, col: usize) -> Vec<(Coin, usize, usize)> {
     let mut neighbors = Vec::new();
 
-    match get_relative_cell(board, row, col, -1, -1) {
-        Some(i) => neighbors.push((i, row - 1, col - 1)),
-        None => (),
+    if let Some(i) = get_relative_cell(position, row, col, -1, -1) {
+        neighbors.push((i, row - 1, col - 1));
     }
 
-    match get_relative_cell(board, row, col, -1, 1) {
-        Some(i) => neighbors.push((i, row - 1, col + 1)),
-        None => (),
+    if let Some(i) = get_relative_cell(position, row, col, -1, 1) {
+        neighbors.push((i, row - 1, col + 1));
     }
 
-    match get_relative_cell(board, row, col, 0, -1) {
-        Some(i) => neighbors.push((i, row, col - 1)),
-        None => (),
+    if let Some(i) = get_relative_cell(position, row, col, 0, -1) {
+        neighbors.push((i, row, col - 1));
     }
 
-    match get_relative_cell(board, row, col, 0, 1) {
-        Some(i) => neighbors.push((i, row, col + 1)),
-        None => (),
+    if let Some(i) = get_relative_cell(position, row, col, 0, 1) {
+        neighbors.push((i, row, col + 1));
     }
 
-    match get_relative_cell(board, row, col, 1, -1) {
-        Some(i) => neighbors.push((i, row, col - 1)),
-        None => (),
+    if let Some(i) = get_relative_cell(position, row, col, 1, -1) {
+        neighbors.push((i, row, col - 1));
     }
 
-    match get_relative_cell(board, row, col, 1, 0) {
-        Some(i) => neighbors.push((i, row, col)),
-        None => (),
+    if let Some(i) = get_relative_cell(position, row, col, 1, 0) {
+        neighbors.push((i, row, col));
     }
 
-    match get_relative_cell(board, row, col, 1, 1) {
-        Some(i) => neighbors.push((i, row, col + 1)),
-        None => (),
+    if let Some(i) = get_relative_cell(position, row, col, 1, 1) {
+        neighbors.push((i, row, col + 1));
     }
 
     neighbors
 }
 
-fn get_legal_moves(board: &Vec<Vec<Coin>>) -> Vec<usize> {
-    let mut moves = Vec::new();
+fn max_depth(position: &Position) -> usize {
+    if position.rules().pop_out {
+        position.cells()
+    } else {
+        position.cells() - position.moves()
+    }
+}
 
-    for i in 0..7 {
-        if board[0][i] == Coin::Empty {
-            moves.push(i);
-        }
+fn search_outcome(position: &Position, score: f64) -> Option<Score> {
+    if score.abs() < WIN_SCORE / 2.0 {
+        return None;
     }
 
-    moves
+    let distance = (WIN_SCORE - score.abs()) as usize - position.moves();
+
+    Some(if score > 0.0 {
+        Score::Win(distance)
+    } else {
+        Score::Loss(distance)
+    })
 }
 
-fn is_full(board: &Vec<Vec<Coin>>) -> bool {
-    for i in board.iter().next().unwrap() {
-        if *i == Coin::Empty {
-            return false;
+fn make_move(position: &mut Position, mv: Move) -> Option<(usize, usize)> {
+    match mv {
+        Move::Drop(col) => Some(position.drop(col, position.current_player())),
+        Move::Pop(col) => {
+            position.pop(col);
+            None
         }
     }
+}
 
-    true
+fn get_ordered_columns(position: &Position) -> Vec<usize> {
+    let mut columns = (0..position.width()).collect::<Vec<_>>();
+    columns.sort_by_key(|i| i.abs_diff(position.width() / 2));
+    columns
 }
 
-fn who_won(board: &Vec<Vec<Coin>>) -> State {
-    for i in board {
-        for j in i.windows(4) {
-            if j[0] == j[1] && j[0] == j[2] && j[0] == j[3] {
-                match j[0] {
-                    Coin::Red => return State::PlayerWon,
-                    Coin::Yellow => return State::ComputerWon,
-                    _ => (),
-                }
-            }
-        }
-    }
+fn get_ordered_moves(position: &Position) -> Vec<Move> {
+    let columns = get_ordered_columns(position);
+    let drops = columns
+        .iter()
+        .filter(|&&i| position.can_play(i))
+        .map(|&i| Move::Drop(i));
+    let pops = columns
+        .iter()
+        .filter(|&&i| position.can_pop(i))
+        .map(|&i| Move::Pop(i));
 
-    for i in 0..3 {
-        for j in 0..6 {
-            let top = &board[i][j];
-            let middle_top = &board[i + 1][j];
-            let middle_bottom = &board[i + 2][j];
-            let bottom = &board[i + 3][j];
+    drops.chain(pops).collect()
+}
 
-            if top == middle_top && top == middle_bottom && top == bottom {
-                match top {
-                    Coin::Red => return State::PlayerWon,
-                    Coin::Yellow => return State::ComputerWon,
-                    _ => (),
-                }
-            }
-        }
+fn who_won(position: &Position) -> State {
+    match position.winner() {
+        Some(Coin::Red) => State::PlayerWon,
+        Some(Coin::Yellow) => State::ComputerWon,
+        _ if position.is_full() => State::Draw,
+        _ => State::None,
     }
+}
 
-    for i in 0..3 {
-        for j in 0..4 {
-            let top_left = &board[i][j];
-            let left = &board[i + 1][j + 1];
-            let right = &board[i + 2][j + 2];
-            let bottom_right = &board[i + 3][j + 3];
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Rules;
 
-            if top_left == left && top_left == right && top_left == bottom_right {
-                match top_left {
-                    Coin::Red => return State::PlayerWon,
-                    Coin::Yellow => return State::ComputerWon,
-                    _ => (),
-                }
-            }
-        }
+    fn solve_moves(moves: &str) -> Score {
+        solve(&Board::from_moves(moves).unwrap()).unwrap()
     }
 
-    for i in 0..3 {
-        for j in 3..7 {
-            let top_right = &board[i][j];
-            let right = &board[i + 1][j - 1];
-            let left = &board[i + 2][j - 2];
-            let bottom_left = &board[i + 3][j - 3];
+    #[test]
+    fn solves_immediate_win() {
+        assert_eq!(solve_moves("121212"), Score::Win(1));
+    }
 
-            if top_right == right && top_right == left && top_right == bottom_left {
-                match top_right {
-                    Coin::Red => return State::PlayerWon,
-                    Coin::Yellow => return State::ComputerWon,
-                    _ => (),
-                }
-            }
-        }
+    #[test]
+    fn solves_finished_game() {
+        assert_eq!(solve_moves("1212121"), Score::Loss(0));
+    }
+
+    #[test]
+    fn solves_late_positions() {
+        assert_eq!(
+            solve_moves("2252576253462244111563365343671351441"),
+            Score::Loss(4)
+        );
+        assert_eq!(
+            solve_moves("7422341735647741166133573473242566"),
+            Score::Win(7)
+        );
+        assert_eq!(
+            solve_moves("65214673556155731566316327373221417"),
+            Score::Loss(6)
+        );
+        assert_eq!(
+            solve_moves("23163416124767223154467471272416755633"),
+            Score::Draw
+        );
     }
 
-    if is_full(board) {
-        return State::Draw;
+    #[test]
+    fn refuses_non_standard_boards() {
+        let popout = Rules {
+            pop_out: true,
+            ..Rules::default()
+        };
+
+        assert_eq!(solve(&Board::with_rules(Rules::new(8, 7))), None);
+        assert_eq!(solve(&Board::with_rules(popout)), None);
     }
 
-    State::None
+    #[test]
+    fn solver_move_matches_score() {
+        let board = Board::from_moves("7422341735647741166133573473242566").unwrap();
+        let (col, score) = get_solver_move_with_score(&board);
+        let mut next = board.clone();
+        next.drop(col).unwrap();
+
+        assert_eq!(score, Score::Win(7));
+        assert_eq!(solve(&next).map(Score::before_move), Some(score));
+    }
 }
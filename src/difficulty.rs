@@ -0,0 +1,97 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::board::Board;
+use crate::engine::{Engine, SearchLimits};
+use crate::game_move::Move;
+use crate::random::Random;
+
+const CASUAL_ITERATIONS: usize = 2000;
+const STRONG_DEPTH: usize = 9;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Beginner,
+    Casual,
+    Strong,
+    Perfect,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Beginner,
+        Difficulty::Casual,
+        Difficulty::Strong,
+        Difficulty::Perfect,
+    ];
+
+    pub fn engine(&self) -> &'static str {
+        match self {
+            Difficulty::Beginner => "old",
+            Difficulty::Casual => "mcts",
+            Difficulty::Strong => "bot",
+            Difficulty::Perfect => "solver",
+        }
+    }
+
+    pub fn limits(&self, think_time: Option<Duration>) -> SearchLimits {
+        match self {
+            Difficulty::Beginner | Difficulty::Perfect => SearchLimits::default(),
+            Difficulty::Casual => SearchLimits {
+                iterations: Some(CASUAL_ITERATIONS),
+                ..SearchLimits::default()
+            },
+            Difficulty::Strong => SearchLimits {
+                depth: Some(STRONG_DEPTH),
+                time: think_time,
+                ..SearchLimits::default()
+            },
+        }
+    }
+
+    pub fn choose_move(
+        &self,
+        board: &Board,
+        engine: &mut dyn Engine,
+        think_time: Option<Duration>,
+        random: &mut Random,
+    ) -> Move {
+        let legal_moves = board.legal_moves();
+
+        if random.next_f64() < self.blunder_chance() {
+            return legal_moves[random.below(legal_moves.len())];
+        }
+
+        engine.choose_move(board, &self.limits(think_time))
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Difficulty::Beginner => "shallow search, blunders often",
+            Difficulty::Casual => "quick simulations, blunders sometimes",
+            Difficulty::Strong => "deep search, no blunders",
+            Difficulty::Perfect => "heuristic before move 12, perfect after on 7x6",
+        }
+    }
+
+    fn blunder_chance(&self) -> f64 {
+        match self {
+            Difficulty::Beginner => 0.25,
+            Difficulty::Casual => 0.1,
+            Difficulty::Strong | Difficulty::Perfect => 0.0,
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Difficulty::Beginner => "Beginner",
+            Difficulty::Casual => "Casual",
+            Difficulty::Strong => "Strong",
+            Difficulty::Perfect => "Perfect",
+        };
+
+        write!(f, "{name}")
+    }
+}
@@ -0,0 +1,61 @@
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::board::Board;
+use crate::book::Book;
+use crate::bot::{AlphaBeta, Score, Solver};
+use crate::game_move::Move;
+use crate::mcts::Mcts;
+use crate::old::Legacy;
+
+pub const DEFAULT_HASH_MB: usize = 64;
+
+#[derive(Clone)]
+pub struct EngineOptions {
+    pub hash_mb: usize,
+    pub book: Option<Rc<Book>>,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct SearchLimits {
+    pub depth: Option<usize>,
+    pub time: Option<Duration>,
+    pub iterations: Option<usize>,
+}
+
+pub trait Engine {
+    fn name(&self) -> &'static str;
+
+    fn choose_move(&mut self, board: &Board, limits: &SearchLimits) -> Move;
+
+    fn analyze(&mut self, _board: &Board, _limits: &SearchLimits) -> Option<Score> {
+        None
+    }
+}
+
+const ENGINES: [&str; 4] = ["bot", "old", "mcts", "solver"];
+
+pub fn names() -> &'static [&'static str] {
+    &ENGINES
+}
+
+pub fn create(name: &str, options: &EngineOptions) -> Option<Box<dyn Engine>> {
+    let engine: Box<dyn Engine> = match name {
+        "bot" => Box::new(AlphaBeta::new(options)),
+        "old" => Box::new(Legacy),
+        "mcts" => Box::new(Mcts::new()),
+        "solver" => Box::new(Solver::new(options)),
+        _ => return None,
+    };
+
+    Some(engine)
+}
+
+impl Default for EngineOptions {
+    fn default() -> Self {
+        Self {
+            hash_mb: DEFAULT_HASH_MB,
+            book: None,
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DropError {
+    ColumnFull(usize),
+    ColumnOutOfRange(usize),
+    GameAlreadyOver,
+    CannotPop(usize),
+}
+
+impl fmt::Display for DropError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DropError::ColumnFull(col) => write!(f, "column {} is full", col + 1),
+            DropError::ColumnOutOfRange(col) => write!(f, "there is no column {}", col + 1),
+            DropError::GameAlreadyOver => write!(f, "the game is already over"),
+            DropError::CannotPop(col) => {
+                write!(f, "cannot pop a coin from column {}", col + 1)
+            }
+        }
+    }
+}
+
+impl Error for DropError {}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NotationError {
+    InvalidCharacter(char),
+    IllegalMove { index: usize, error: DropError },
+    UnsupportedBoard,
+    MissingHistory,
+    InvalidSize,
+    FloatingCoin { row: usize, col: usize },
+    CoinCount,
+    PlayAfterWin,
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotationError::InvalidCharacter(c) => write!(f, "unexpected character '{c}'"),
+            NotationError::IllegalMove { index, error } => {
+                write!(f, "move {} is illegal: {}", index + 1, error)
+            }
+            NotationError::UnsupportedBoard => {
+                write!(f, "move strings only support boards up to 9 columns wide")
+            }
+            NotationError::MissingHistory => {
+                write!(f, "the board was set up without a move history")
+            }
+            NotationError::InvalidSize => write!(f, "the grid is not a supported board size"),
+            NotationError::FloatingCoin { row, col } => write!(
+                f,
+                "the coin in row {}, column {} has nothing below it",
+                row + 1,
+                col + 1
+            ),
+            NotationError::CoinCount => {
+                write!(f, "the coin counts do not match the player to move")
+            }
+            NotationError::PlayAfterWin => write!(f, "play continued after the game was won"),
+        }
+    }
+}
+
+impl Error for NotationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            NotationError::IllegalMove { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RecordError {
+    Io(io::Error),
+    InvalidHeader(String),
+    InvalidMove(String),
+    InvalidRules,
+    Notation(NotationError),
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordError::Io(error) => write!(f, "{error}"),
+            RecordError::InvalidHeader(line) => write!(f, "invalid header: {line}"),
+            RecordError::InvalidMove(text) => write!(f, "invalid move: {text}"),
+            RecordError::InvalidRules => write!(f, "the game uses unsupported rules"),
+            RecordError::Notation(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for RecordError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RecordError::Io(error) => Some(error),
+            RecordError::Notation(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RecordError {
+    fn from(error: io::Error) -> Self {
+        RecordError::Io(error)
+    }
+}
+
+impl From<NotationError> for RecordError {
+    fn from(error: NotationError) -> Self {
+        RecordError::Notation(error)
+    }
+}
@@ -0,0 +1,42 @@
+use std::fmt;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Move {
+    Drop(usize),
+    Pop(usize),
+}
+
+impl Move {
+    pub fn column(&self) -> usize {
+        match self {
+            Move::Drop(col) | Move::Pop(col) => *col,
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        let (pop, column) = match text.strip_prefix(['p', 'P']) {
+            Some(column) => (true, column),
+            None => (false, text),
+        };
+
+        let column = match column.trim().parse::<usize>() {
+            Ok(i) if i > 0 => i - 1,
+            _ => return None,
+        };
+
+        Some(if pop {
+            Move::Pop(column)
+        } else {
+            Move::Drop(column)
+        })
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Move::Drop(col) => write!(f, "{}", col + 1),
+            Move::Pop(col) => write!(f, "p{}", col + 1),
+        }
+    }
+}
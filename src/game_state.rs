@@ -5,3 +5,27 @@ pub enum GameState {
     Draw,
     OnGoing,
 }
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct WinningLine {
+    pub start: (usize, usize),
+    pub direction: (isize, isize),
+    pub length: usize,
+}
+
+impl WinningLine {
+    pub fn cells(&self) -> Vec<(usize, usize)> {
+        (0..self.length as isize)
+            .map(|i| {
+                (
+                    (self.start.0 as isize + self.direction.0 * i) as usize,
+                    (self.start.1 as isize + self.direction.1 * i) as usize,
+                )
+            })
+            .collect()
+    }
+
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        self.cells().contains(&(row, col))
+    }
+}
@@ -0,0 +1,32 @@
+pub mod board;
+pub mod book;
+mod bot;
+pub mod coin;
+pub mod difficulty;
+pub mod engine;
+pub mod error;
+pub mod game_move;
+pub mod game_state;
+mod mcts;
+mod notation;
+mod old;
+pub mod position;
+pub mod random;
+pub mod record;
+pub mod rules;
+mod transposition;
+
+pub use board::Board;
+pub use bot::{
+    analyze, best_analysis, get_computer_move, get_computer_move_timed, hint, move_reason, search,
+    solve, ColumnAnalysis, Hint, LineKind, MoveReason, Score, SearchInfo,
+};
+pub use coin::Coin;
+pub use engine::{Engine, EngineOptions, SearchLimits};
+pub use error::{DropError, NotationError, RecordError};
+pub use game_move::Move;
+pub use game_state::{GameState, WinningLine};
+pub use position::Position;
+pub use record::GameRecord;
+pub use rules::Rules;
+pub use transposition::TranspositionTable;
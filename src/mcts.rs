@@ -0,0 +1,200 @@
+use crate::board::Board;
+use crate::coin::Coin;
+use crate::engine::{Engine, SearchLimits};
+use crate::game_move::Move;
+use crate::position::Position;
+use crate::random::Random;
+
+const EXPLORATION: f64 = std::f64::consts::SQRT_2;
+const DEFAULT_ITERATIONS: usize = 20000;
+// PopOut games can go on forever, so playouts longer than this are scored as draws.
+const PLAYOUT_LENGTH_FACTOR: usize = 4;
+
+pub struct Mcts {
+    random: Random,
+}
+
+struct Node {
+    position: Position,
+    mv: Move,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<Move>,
+    visits: f64,
+    wins: f64,
+}
+
+pub fn get_computer_move(board: &Board, iterations: usize, random: &mut Random) -> Move {
+    let mut tree = vec![Node::new(*board.position(), Move::Drop(0), None)];
+
+    for _ in 0..iterations.max(1) {
+        let mut node = select(&tree);
+
+        if !tree[node].untried.is_empty() {
+            node = expand(&mut tree, node, random);
+        }
+
+        let winner = simulate(tree[node].position, random);
+        backpropagate(&mut tree, node, winner);
+    }
+
+    tree[0]
+        .children
+        .iter()
+        .max_by(|&&x, &&y| tree[x].visits.total_cmp(&tree[y].visits))
+        .map(|&child| tree[child].mv)
+        .unwrap_or_else(|| board.legal_moves()[0])
+}
+
+fn select(tree: &[Node]) -> usize {
+    let mut node = 0;
+
+    while tree[node].untried.is_empty() && !tree[node].children.is_empty() {
+        let parent_visits = tree[node].visits.ln();
+
+        node = *tree[node]
+            .children
+            .iter()
+            .max_by(|&&x, &&y| {
+                let x = tree[x].uct(parent_visits);
+                x.total_cmp(&tree[y].uct(parent_visits))
+            })
+            .unwrap();
+    }
+
+    node
+}
+
+fn expand(tree: &mut Vec<Node>, node: usize, random: &mut Random) -> usize {
+    let index = random.below(tree[node].untried.len());
+    let mv = tree[node].untried.swap_remove(index);
+
+    let mut position = tree[node].position;
+    position.play_move(mv);
+
+    let child = tree.len();
+    tree.push(Node::new(position, mv, Some(node)));
+    tree[node].children.push(child);
+
+    child
+}
+
+fn simulate(mut position: Position, random: &mut Random) -> Option<Coin> {
+    for _ in 0..position.cells() * PLAYOUT_LENGTH_FACTOR {
+        if let Some(winner) = position.winner() {
+            return Some(winner);
+        }
+
+        if position.is_blocked() {
+            return None;
+        }
+
+        let mv = playout_move(&position, random);
+        position.play_move(mv);
+    }
+
+    None
+}
+
+fn backpropagate(tree: &mut [Node], mut node: usize, winner: Option<Coin>) {
+    loop {
+        let mover = if tree[node].position.current_player() {
+            Coin::Yellow
+        } else {
+            Coin::Red
+        };
+
+        tree[node].visits += 1.0;
+        tree[node].wins += match winner {
+            Some(coin) if coin == mover => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        };
+
+        match tree[node].parent {
+            Some(parent) => node = parent,
+            None => break,
+        }
+    }
+}
+
+fn playout_move(position: &Position, random: &mut Random) -> Move {
+    let moves = position.legal_moves();
+    let mover = if position.current_player() {
+        Coin::Red
+    } else {
+        Coin::Yellow
+    };
+
+    for &i in &moves {
+        let mut next = *position;
+        next.play_move(i);
+
+        if next.winner() == Some(mover) {
+            return i;
+        }
+    }
+
+    let non_losing = position.possible_non_losing_moves();
+    let safe_moves = moves
+        .iter()
+        .copied()
+        .filter(|&i| match i {
+            Move::Drop(col) => non_losing & position.column_mask(col) != 0,
+            Move::Pop(_) => {
+                let mut next = *position;
+                next.play_move(i);
+                next.winner().is_none() && !next.can_win_next()
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if safe_moves.is_empty() {
+        moves[random.below(moves.len())]
+    } else {
+        safe_moves[random.below(safe_moves.len())]
+    }
+}
+
+impl Mcts {
+    pub fn new() -> Self {
+        Self {
+            random: Random::new(),
+        }
+    }
+}
+
+impl Engine for Mcts {
+    fn name(&self) -> &'static str {
+        "mcts"
+    }
+
+    fn choose_move(&mut self, board: &Board, limits: &SearchLimits) -> Move {
+        let iterations = limits.iterations.unwrap_or(DEFAULT_ITERATIONS);
+        get_computer_move(board, iterations, &mut self.random)
+    }
+}
+
+impl Node {
+    fn new(position: Position, mv: Move, parent: Option<usize>) -> Self {
+        let untried = if position.winner().is_some() {
+            Vec::new()
+        } else {
+            position.legal_moves()
+        };
+
+        Self {
+            position,
+            mv,
+            parent,
+            children: Vec::new(),
+            untried,
+            visits: 0.0,
+            wins: 0.0,
+        }
+    }
+
+    fn uct(&self, parent_visits: f64) -> f64 {
+        self.wins / self.visits + EXPLORATION * (parent_visits / self.visits).sqrt()
+    }
+}
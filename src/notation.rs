@@ -0,0 +1,417 @@
+use crate::board::Board;
+use crate::coin::Coin;
+use crate::error::NotationError;
+use crate::game_move::Move;
+use crate::position::Position;
+use crate::rules::{Rules, MAX_WIDTH};
+
+const MAX_MOVE_STRING_WIDTH: usize = 9;
+
+impl Board {
+    pub fn from_moves(moves: &str) -> Result<Self, NotationError> {
+        let mut board = Board::new();
+        board.play_moves(moves)?;
+        Ok(board)
+    }
+
+    pub fn play_moves(&mut self, moves: &str) -> Result<(), NotationError> {
+        let mut pop = false;
+        let mut index = 0;
+
+        for c in moves.chars().filter(|c| !c.is_whitespace()) {
+            if c == 'p' && !pop {
+                pop = true;
+                continue;
+            }
+
+            let col = match c.to_digit(10) {
+                Some(i) if i > 0 => i as usize - 1,
+                _ => return Err(NotationError::InvalidCharacter(c)),
+            };
+
+            let mv = if pop { Move::Pop(col) } else { Move::Drop(col) };
+
+            self.play_move(mv)
+                .map_err(|error| NotationError::IllegalMove { index, error })?;
+
+            pop = false;
+            index += 1;
+        }
+
+        if pop {
+            return Err(NotationError::InvalidCharacter('p'));
+        }
+
+        Ok(())
+    }
+
+    pub fn to_moves(&self) -> Result<String, NotationError> {
+        if self.width() > MAX_MOVE_STRING_WIDTH {
+            return Err(NotationError::UnsupportedBoard);
+        }
+
+        if self.history().len() != self.position().moves() {
+            return Err(NotationError::MissingHistory);
+        }
+
+        Ok(self.history().iter().map(Move::to_string).collect())
+    }
+
+    pub fn from_grid(grid: &str) -> Result<Self, NotationError> {
+        let (rows, side) = parse_grid(grid)?;
+        let rules = Rules::new(rows[0].len(), rows.len());
+
+        grid_board(&rows, side, rules)
+    }
+
+    pub fn from_grid_with_rules(grid: &str, rules: Rules) -> Result<Self, NotationError> {
+        let (rows, side) = parse_grid(grid)?;
+
+        grid_board(&rows, side, rules)
+    }
+
+    pub fn grid_size(grid: &str) -> Result<(usize, usize), NotationError> {
+        let (rows, _) = parse_grid(grid)?;
+        let (width, height) = (rows[0].len(), rows.len());
+
+        if rows.iter().any(|row| row.len() != width) || !Rules::new(width, height).is_valid() {
+            return Err(NotationError::InvalidSize);
+        }
+
+        Ok((width, height))
+    }
+
+    pub fn to_grid(&self) -> String {
+        let rows = self
+            .get_board()
+            .iter()
+            .map(|cells| {
+                let mut row = String::new();
+                let mut empty = 0;
+
+                for cell in cells {
+                    if *cell == Coin::Empty {
+                        empty += 1;
+                        continue;
+                    }
+
+                    if empty > 0 {
+                        row += &empty.to_string();
+                        empty = 0;
+                    }
+
+                    row.push(if *cell == Coin::Red { 'r' } else { 'y' });
+                }
+
+                if empty > 0 {
+                    row += &empty.to_string();
+                }
+
+                row
+            })
+            .collect::<Vec<_>>();
+
+        let side = if self.position().current_player() {
+            "r"
+        } else {
+            "y"
+        };
+
+        format!("{} {}", rows.join("/"), side)
+    }
+}
+
+fn parse_grid(grid: &str) -> Result<(Vec<Vec<Coin>>, Option<bool>), NotationError> {
+    let mut fields = grid.split_whitespace();
+    let rows = parse_rows(fields.next().unwrap_or(""))?;
+    let side = match fields.next() {
+        Some("r") => Some(true),
+        Some("y") => Some(false),
+        Some(side) => {
+            return Err(NotationError::InvalidCharacter(
+                side.chars().next().unwrap(),
+            ))
+        }
+        None => None,
+    };
+
+    if let Some(field) = fields.next() {
+        return Err(NotationError::InvalidCharacter(
+            field.chars().next().unwrap(),
+        ));
+    }
+
+    Ok((rows, side))
+}
+
+fn grid_board(
+    rows: &[Vec<Coin>],
+    side: Option<bool>,
+    rules: Rules,
+) -> Result<Board, NotationError> {
+    if !rules.is_valid()
+        || rows.len() != rules.height
+        || rows.iter().any(|row| row.len() != rules.width)
+    {
+        return Err(NotationError::InvalidSize);
+    }
+
+    for (row, cells) in rows.iter().enumerate().take(rules.height - 1) {
+        for (col, cell) in cells.iter().enumerate() {
+            if *cell != Coin::Empty && rows[row + 1][col] == Coin::Empty {
+                return Err(NotationError::FloatingCoin { row, col });
+            }
+        }
+    }
+
+    let count = |coin| rows.iter().flatten().filter(|&&cell| cell == coin).count();
+    let (red, yellow) = (count(Coin::Red), count(Coin::Yellow));
+    let red_to_move = red == yellow;
+
+    if (!red_to_move && red != yellow + 1) || side.is_some_and(|side| side != red_to_move) {
+        return Err(NotationError::CoinCount);
+    }
+
+    let position = build_position(rows, rules, None);
+
+    if let Some(winner) = position.winner() {
+        let last_mover = if red_to_move { Coin::Yellow } else { Coin::Red };
+        let last_move_wins = (0..rules.width).any(|col| {
+            let row = rows.iter().position(|cells| cells[col] != Coin::Empty);
+
+            row.is_some_and(|row| {
+                rows[row][col] == winner
+                    && build_position(rows, rules, Some((row, col)))
+                        .winner()
+                        .is_none()
+            })
+        });
+
+        if winner != last_mover || !last_move_wins {
+            return Err(NotationError::PlayAfterWin);
+        }
+    }
+
+    Ok(Board::from_position(position))
+}
+
+fn parse_rows(text: &str) -> Result<Vec<Vec<Coin>>, NotationError> {
+    let mut rows = Vec::new();
+
+    for line in text.split('/') {
+        let mut row = Vec::new();
+        let mut empty: usize = 0;
+
+        for c in line.chars() {
+            if let Some(i) = c.to_digit(10) {
+                empty = empty
+                    .checked_mul(10)
+                    .and_then(|empty| empty.checked_add(i as usize))
+                    .filter(|&empty| row.len() + empty <= MAX_WIDTH)
+                    .ok_or(NotationError::InvalidSize)?;
+                continue;
+            }
+
+            row.extend(std::iter::repeat_n(Coin::Empty, empty));
+            empty = 0;
+
+            row.push(match c {
+                'r' => Coin::Red,
+                'y' => Coin::Yellow,
+                _ => return Err(NotationError::InvalidCharacter(c)),
+            });
+
+            if row.len() > MAX_WIDTH {
+                return Err(NotationError::InvalidSize);
+            }
+        }
+
+        row.extend(std::iter::repeat_n(Coin::Empty, empty));
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+fn build_position(rows: &[Vec<Coin>], rules: Rules, skip: Option<(usize, usize)>) -> Position {
+    let mut position = Position::with_rules(rules);
+
+    for col in 0..rules.width {
+        for (row, cells) in rows.iter().enumerate().rev() {
+            let coin = cells[col];
+
+            if coin != Coin::Empty && skip != Some((row, col)) {
+                position.drop(col, coin == Coin::Red);
+            }
+        }
+    }
+
+    position
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::DropError;
+
+    fn popout_rules() -> Rules {
+        Rules {
+            pop_out: true,
+            ..Rules::default()
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_characters() {
+        assert_eq!(
+            Board::from_moves("44x").err(),
+            Some(NotationError::InvalidCharacter('x'))
+        );
+        assert_eq!(
+            Board::from_moves("440").err(),
+            Some(NotationError::InvalidCharacter('0'))
+        );
+        assert_eq!(
+            Board::with_rules(popout_rules()).play_moves("44p"),
+            Err(NotationError::InvalidCharacter('p'))
+        );
+        assert_eq!(
+            Board::from_grid("7/7/7/7/7/z6 y").err(),
+            Some(NotationError::InvalidCharacter('z'))
+        );
+        assert_eq!(
+            Board::from_grid("7/7/7/7/7/7 b").err(),
+            Some(NotationError::InvalidCharacter('b'))
+        );
+    }
+
+    #[test]
+    fn rejects_illegal_moves() {
+        assert_eq!(
+            Board::from_moves("1111111").err(),
+            Some(NotationError::IllegalMove {
+                index: 6,
+                error: DropError::ColumnFull(0),
+            })
+        );
+        assert_eq!(
+            Board::from_moves("48").err(),
+            Some(NotationError::IllegalMove {
+                index: 1,
+                error: DropError::ColumnOutOfRange(7),
+            })
+        );
+        assert_eq!(
+            Board::from_moves("4p4").err(),
+            Some(NotationError::IllegalMove {
+                index: 1,
+                error: DropError::CannotPop(3),
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_wide_boards_in_move_strings() {
+        let board = Board::with_rules(Rules::new(10, 8));
+
+        assert_eq!(board.to_moves(), Err(NotationError::UnsupportedBoard));
+    }
+
+    #[test]
+    fn rejects_move_strings_without_history() {
+        let board = Board::from_grid("7/7/7/7/7/3r3 y").unwrap();
+
+        assert_eq!(board.to_moves(), Err(NotationError::MissingHistory));
+    }
+
+    #[test]
+    fn rejects_invalid_grid_sizes() {
+        assert_eq!(
+            Board::from_grid("7/7/7 r").err(),
+            Some(NotationError::InvalidSize)
+        );
+        assert_eq!(
+            Board::from_grid("7/7/7/7/7/6 r").err(),
+            Some(NotationError::InvalidSize)
+        );
+        assert_eq!(
+            Board::from_grid_with_rules("7/7/7/7/7/7 r", Rules::new(8, 6)).err(),
+            Some(NotationError::InvalidSize)
+        );
+        assert_eq!(
+            Board::grid_size("7/7/7/7/7/6"),
+            Err(NotationError::InvalidSize)
+        );
+        assert_eq!(
+            Board::from_grid("100000000000 r").err(),
+            Some(NotationError::InvalidSize)
+        );
+        assert_eq!(
+            Board::from_grid("99999999999999999999999 r").err(),
+            Some(NotationError::InvalidSize)
+        );
+        assert_eq!(
+            Board::from_grid(&format!("{} r", "r".repeat(1000))).err(),
+            Some(NotationError::InvalidSize)
+        );
+    }
+
+    #[test]
+    fn rejects_floating_coins() {
+        assert_eq!(
+            Board::from_grid("7/7/7/7/r6/7 y").err(),
+            Some(NotationError::FloatingCoin { row: 4, col: 0 })
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_coin_counts() {
+        assert_eq!(
+            Board::from_grid("7/7/7/7/7/rr5").err(),
+            Some(NotationError::CoinCount)
+        );
+        assert_eq!(
+            Board::from_grid("7/7/7/7/7/r6 r").err(),
+            Some(NotationError::CoinCount)
+        );
+    }
+
+    #[test]
+    fn rejects_play_after_win() {
+        assert_eq!(
+            Board::from_grid("7/7/7/7/yy5/rrrryy1").err(),
+            Some(NotationError::PlayAfterWin)
+        );
+        assert!(Board::from_grid("7/7/7/7/yyy4/rrrr3 y").is_ok());
+    }
+
+    #[test]
+    fn move_strings_round_trip() {
+        for moves in ["", "4", "4455667", "123456712345"] {
+            assert_eq!(Board::from_moves(moves).unwrap().to_moves().unwrap(), moves);
+        }
+
+        let mut board = Board::with_rules(popout_rules());
+        board.play_moves("1121p1p1").unwrap();
+
+        assert_eq!(board.to_moves().unwrap(), "1121p1p1");
+    }
+
+    #[test]
+    fn grids_round_trip() {
+        for grid in [
+            "7/7/7/7/7/7 r",
+            "7/7/7/7/3y3/3r3 r",
+            "7/7/7/7/yyy4/rrrr3 y",
+            "10/10/10/10/10/10/10/r8y r",
+        ] {
+            assert_eq!(Board::from_grid(grid).unwrap().to_grid(), grid);
+        }
+
+        let board = Board::from_moves("4455667").unwrap();
+        let copy = Board::from_grid(&board.to_grid()).unwrap();
+
+        assert!(copy.position() == board.position());
+        assert_eq!(copy.get_board(), board.get_board());
+    }
+}
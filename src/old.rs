@@ -1,16 +1,50 @@
 use std::collections::HashMap;
 
+use crate::board::Board;
 use crate::coin::Coin;
+use crate::engine::{Engine, SearchLimits};
+use crate::game_move::Move;
 
-pub fn get_computer_move(board: Vec<Vec<Coin>>) -> u8 {
-    get_best_move(&board).0
+pub struct Legacy;
+
+impl Engine for Legacy {
+    fn name(&self) -> &'static str {
+        "old"
+    }
+
+    fn choose_move(&mut self, board: &Board, _limits: &SearchLimits) -> Move {
+        let legal_moves = board.legal_moves();
+
+        // The legacy search only knows drops, so a full PopOut board falls back to a pop.
+        if !legal_moves.iter().any(|mv| matches!(mv, Move::Drop(_))) {
+            return legal_moves[0];
+        }
+
+        let mut board_vec = board.get_board();
+
+        if board.position().current_player() {
+            for cell in board_vec.iter_mut().flatten() {
+                *cell = match cell {
+                    Coin::Empty => Coin::Empty,
+                    Coin::Red => Coin::Yellow,
+                    Coin::Yellow => Coin::Red,
+                };
+            }
+        }
+
+        Move::Drop(get_computer_move(board_vec, board.rules().win_length) as usize)
+    }
+}
+
+pub fn get_computer_move(board: Vec<Vec<Coin>>, win_length: usize) -> u8 {
+    get_best_move(&board, win_length).0
 }
 
-fn get_best_move(board: &Vec<Vec<Coin>>) -> (u8, f64) {
+fn get_best_move(board: &[Vec<Coin>], win_length: usize) -> (u8, f64) {
     let mut moves_rating = HashMap::<u8, f64>::new();
 
     for i in get_legal_moves(board) {
-        moves_rating.insert(i, evaluate_for_move(board, i, false));
+        moves_rating.insert(i, evaluate_for_move(board, i, false, win_length));
     }
 
     let (col, eval) = moves_rating
@@ -21,13 +55,13 @@ fn get_best_move(board: &Vec<Vec<Coin>>) -> (u8, f64) {
     (*col, *eval)
 }
 
-fn evaluate_for_move(board: &Vec<Vec<Coin>>, after_move: u8, turn: bool) -> f64 {
-    let mut board_copy = board.clone();
+fn evaluate_for_move(board: &[Vec<Coin>], after_move: u8, turn: bool, win_length: usize) -> f64 {
+    let mut board_copy = board.to_vec();
     let (comp_choice_row, comp_choice_col) = drop(&mut board_copy, after_move as usize, turn);
 
     let mut count = 0.0;
 
-    if computer_won(&board_copy) {
+    if computer_won(&board_copy, win_length) {
         return -f64::INFINITY;
     }
 
@@ -36,7 +70,7 @@ fn evaluate_for_move(board: &Vec<Vec<Coin>>, after_move: u8, turn: bool) -> f64
     for i in opponent_moves {
         let (placed_row, placed_col) = drop(&mut board_copy, i as usize, true);
 
-        if player_won(&board_copy) {
+        if player_won(&board_copy, win_length) {
             return f64::INFINITY;
         }
 
@@ -60,11 +94,11 @@ fn evaluate_for_move(board: &Vec<Vec<Coin>>, after_move: u8, turn: bool) -> f64
     count
 }
 
-fn get_legal_moves(board: &Vec<Vec<Coin>>) -> Vec<u8> {
+fn get_legal_moves(board: &[Vec<Coin>]) -> Vec<u8> {
     let mut moves = Vec::new();
 
-    for i in 0..7 {
-        if board[0][i] == Coin::Empty {
+    for (i, cell) in board[0].iter().enumerate() {
+        if *cell == Coin::Empty {
             moves.push(i as u8);
         }
     }
@@ -72,10 +106,10 @@ fn get_legal_moves(board: &Vec<Vec<Coin>>) -> Vec<u8> {
     moves
 }
 
-fn drop(board: &mut Vec<Vec<Coin>>, col: usize, turn: bool) -> (usize, usize) {
+fn drop(board: &mut [Vec<Coin>], col: usize, turn: bool) -> (usize, usize) {
     let mut row = 0;
 
-    while row + 1 < 6 {
+    while row + 1 < board.len() {
         if board[row + 1][col] == Coin::Empty {
             row += 1;
         } else {
@@ -88,161 +122,71 @@ fn drop(board: &mut Vec<Vec<Coin>>, col: usize, turn: bool) -> (usize, usize) {
 }
 
 fn get_neighboring_cells(
-    board: &Vec<Vec<Coin>>,
+    board: &[Vec<Coin>],
     row: usize,
     col: usize,
 ) -> Vec<(&Coin, usize, usize)> {
     let mut neighbors = Vec::new();
+    let height = board.len();
+    let width = board[0].len();
 
-    if (row as isize) - 1 >= 0 && (col as isize) - 1 >= 0 {
+    if row >= 1 && col >= 1 {
         neighbors.push((&board[row - 1][col - 1], row - 1, col - 1));
     }
 
-    if (row as isize) - 1 >= 0 && (col as isize) + 1 < 7 {
+    if row >= 1 && col + 1 < width {
         neighbors.push((&board[row - 1][col + 1], row - 1, col + 1));
     }
 
-    if (col as isize) - 1 >= 0 {
+    if col >= 1 {
         neighbors.push((&board[row][col - 1], row, col - 1));
     }
 
-    if (col as isize) + 1 < 7 {
+    if col + 1 < width {
         neighbors.push((&board[row][col + 1], row, col + 1));
     }
 
-    if (row as isize) + 1 < 6 && (col as isize) - 1 >= 0 {
+    if row + 1 < height && col >= 1 {
         neighbors.push((&board[row + 1][col - 1], row + 1, col - 1));
     }
 
-    if (row as isize) + 1 < 6 {
+    if row + 1 < height {
         neighbors.push((&board[row + 1][col], row + 1, col));
     }
 
-    if (row as isize) + 1 < 6 && (col as isize) + 1 < 7 {
+    if row + 1 < height && col + 1 < width {
         neighbors.push((&board[row + 1][col + 1], row + 1, col + 1));
     }
 
     neighbors
 }
 
-fn player_won(board: &Vec<Vec<Coin>>) -> bool {
-    for i in board {
-        for j in i.windows(4) {
-            if j[0] == j[1] && j[0] == j[2] && j[0] == j[3] {
-                match j[0] {
-                    Coin::Red => return true,
-                    _ => (),
-                }
-            }
-        }
-    }
-
-    for i in 0..3 {
-        for j in 0..6 {
-            let top = &board[i][j];
-            let middle_top = &board[i + 1][j];
-            let middle_bottom = &board[i + 2][j];
-            let bottom = &board[i + 3][j];
-
-            if top == middle_top && top == middle_bottom && top == bottom {
-                match top {
-                    Coin::Red => return true,
-                    _ => (),
-                }
-            }
-        }
-    }
-
-    for i in 0..3 {
-        for j in 0..4 {
-            let top_left = &board[i][j];
-            let left = &board[i + 1][j + 1];
-            let right = &board[i + 2][j + 2];
-            let bottom_right = &board[i + 3][j + 3];
-
-            if top_left == left && top_left == right && top_left == bottom_right {
-                match top_left {
-                    Coin::Red => return true,
-                    _ => (),
-                }
-            }
-        }
-    }
-
-    for i in 0..3 {
-        for j in 3..7 {
-            let top_right = &board[i][j];
-            let right = &board[i + 1][j - 1];
-            let left = &board[i + 2][j - 2];
-            let bottom_left = &board[i + 3][j - 3];
-
-            if top_right == right && top_right == left && top_right == bottom_left {
-                match top_right {
-                    Coin::Red => return true,
-                    _ => (),
-                }
-            }
-        }
-    }
+fn player_won(board: &[Vec<Coin>], length: usize) -> bool {
+    has_line(board, Coin::Red, length)
+}
 
-    false
+fn computer_won(board: &[Vec<Coin>], length: usize) -> bool {
+    has_line(board, Coin::Yellow, length)
 }
 
-fn computer_won(board: &Vec<Vec<Coin>>) -> bool {
-    for i in board {
-        for j in i.windows(4) {
-            if j[0] == j[1] && j[0] == j[2] && j[0] == j[3] {
-                match j[0] {
-                    Coin::Yellow => return true,
-                    _ => (),
-                }
-            }
-        }
-    }
+fn has_line(board: &[Vec<Coin>], coin: Coin, length: usize) -> bool {
+    let height = board.len() as isize;
+    let width = board[0].len() as isize;
 
-    for i in 0..3 {
-        for j in 0..6 {
-            let top = &board[i][j];
-            let middle_top = &board[i + 1][j];
-            let middle_bottom = &board[i + 2][j];
-            let bottom = &board[i + 3][j];
-
-            if top == middle_top && top == middle_bottom && top == bottom {
-                match top {
-                    Coin::Yellow => return true,
-                    _ => (),
-                }
-            }
-        }
-    }
+    for row in 0..height {
+        for col in 0..width {
+            for (row_shift, col_shift) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+                let line = (0..length as isize).all(|i| {
+                    let row = row + row_shift * i;
+                    let col = col + col_shift * i;
 
-    for i in 0..3 {
-        for j in 0..4 {
-            let top_left = &board[i][j];
-            let left = &board[i + 1][j + 1];
-            let right = &board[i + 2][j + 2];
-            let bottom_right = &board[i + 3][j + 3];
-
-            if top_left == left && top_left == right && top_left == bottom_right {
-                match top_left {
-                    Coin::Yellow => return true,
-                    _ => (),
-                }
-            }
-        }
-    }
+                    (0..height).contains(&row)
+                        && (0..width).contains(&col)
+                        && board[row as usize][col as usize] == coin
+                });
 
-    for i in 0..3 {
-        for j in 3..7 {
-            let top_right = &board[i][j];
-            let right = &board[i + 1][j - 1];
-            let left = &board[i + 2][j - 2];
-            let bottom_left = &board[i + 3][j - 3];
-
-            if top_right == right && top_right == left && top_right == bottom_left {
-                match top_right {
-                    Coin::Yellow => return true,
-                    _ => (),
+                if line {
+                    return true;
                 }
             }
         }
@@ -251,7 +195,7 @@ fn computer_won(board: &Vec<Vec<Coin>>) -> bool {
     false
 }
 
-fn is_full(board: &Vec<Vec<Coin>>) -> bool {
+fn is_full(board: &[Vec<Coin>]) -> bool {
     for i in board.iter().next().unwrap() {
         if *i == Coin::Empty {
             return false;
@@ -260,3 +204,22 @@ fn is_full(board: &Vec<Vec<Coin>>) -> bool {
 
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Rules;
+
+    #[test]
+    fn pops_on_full_popout_board() {
+        let rules = Rules {
+            pop_out: true,
+            ..Rules::new(4, 4)
+        };
+        let board = Board::from_grid_with_rules("rryy/yyrr/rryy/yyrr r", rules).unwrap();
+        let mv = Legacy.choose_move(&board, &SearchLimits::default());
+
+        assert!(matches!(mv, Move::Pop(_)));
+        assert!(board.legal_moves().contains(&mv));
+    }
+}
@@ -0,0 +1,427 @@
+use crate::coin::Coin;
+use crate::game_move::Move;
+use crate::game_state::WinningLine;
+use crate::rules::Rules;
+
+pub const BOARD_WIDTH: usize = 7;
+pub const BOARD_HEIGHT: usize = 6;
+
+const MAX_WIN_LENGTH: usize = 32;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    red: u128,
+    yellow: u128,
+    moves: usize,
+    rules: Rules,
+    bottom_mask: u128,
+    board_mask: u128,
+}
+
+impl Position {
+    pub fn with_rules(rules: Rules) -> Self {
+        assert!(
+            rules.is_valid(),
+            "unsupported rules: {}x{} board, connect {}",
+            rules.width,
+            rules.height,
+            rules.win_length
+        );
+
+        let bottom_mask =
+            (0..rules.width).fold(0, |mask, col| mask | 1 << (col * (rules.height + 1)));
+
+        Self {
+            red: 0,
+            yellow: 0,
+            moves: 0,
+            rules,
+            bottom_mask,
+            board_mask: bottom_mask * ((1 << rules.height) - 1),
+        }
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn width(&self) -> usize {
+        self.rules.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.rules.height
+    }
+
+    pub fn win_length(&self) -> usize {
+        self.rules.win_length
+    }
+
+    pub fn is_standard(&self) -> bool {
+        self.rules.is_standard()
+    }
+
+    pub fn can_play(&self, col: usize) -> bool {
+        col < self.width() && self.mask() & self.top_mask(col) == 0
+    }
+
+    pub(crate) fn drop(&mut self, col: usize, turn: bool) -> (usize, usize) {
+        let mask = self.mask();
+        let placed = (mask + self.bottom_mask(col)) & self.column_mask(col);
+
+        if turn {
+            self.red |= placed;
+        } else {
+            self.yellow |= placed;
+        }
+
+        self.moves += 1;
+
+        let height = (placed.trailing_zeros() as usize) % self.column_size();
+        (self.height() - 1 - height, col)
+    }
+
+    pub(crate) fn pop(&mut self, col: usize) {
+        let column = self.column_mask(col);
+
+        self.red = (self.red & !column) | ((self.red & column) >> 1 & column);
+        self.yellow = (self.yellow & !column) | ((self.yellow & column) >> 1 & column);
+        self.moves += 1;
+    }
+
+    pub fn can_pop(&self, col: usize) -> bool {
+        self.rules.pop_out && col < self.width() && self.current() & self.bottom_mask(col) != 0
+    }
+
+    pub(crate) fn play_move(&mut self, mv: Move) {
+        match mv {
+            Move::Drop(col) => {
+                self.drop(col, self.current_player());
+            }
+            Move::Pop(col) => self.pop(col),
+        }
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let drops = (0..self.width())
+            .filter(|&col| self.can_play(col))
+            .map(Move::Drop);
+        let pops = (0..self.width())
+            .filter(|&col| self.can_pop(col))
+            .map(Move::Pop);
+
+        drops.chain(pops).collect()
+    }
+
+    pub(crate) fn play(&mut self, placed: u128) {
+        if self.current_player() {
+            self.red |= placed;
+        } else {
+            self.yellow |= placed;
+        }
+
+        self.moves += 1;
+    }
+
+    pub fn cell(&self, row: usize, col: usize) -> Coin {
+        let bit = self.cell_mask(row, col);
+
+        if self.red & bit != 0 {
+            Coin::Red
+        } else if self.yellow & bit != 0 {
+            Coin::Yellow
+        } else {
+            Coin::Empty
+        }
+    }
+
+    pub fn winner(&self) -> Option<Coin> {
+        match (self.has_line(self.red), self.has_line(self.yellow)) {
+            // A pop can complete lines for both players, the player who popped wins.
+            (true, true) if self.current_player() => Some(Coin::Yellow),
+            (true, true) => Some(Coin::Red),
+            (true, false) => Some(Coin::Red),
+            (false, true) => Some(Coin::Yellow),
+            (false, false) => None,
+        }
+    }
+
+    pub fn winning_line(&self) -> Option<WinningLine> {
+        let winner = self.winner()?;
+        let (height, width) = (self.height() as isize, self.width() as isize);
+        let is_winner = |row: isize, col: isize| {
+            (0..height).contains(&row)
+                && (0..width).contains(&col)
+                && self.cell(row as usize, col as usize) == winner
+        };
+
+        for row in 0..height {
+            for col in 0..width {
+                for (row_step, col_step) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+                    if !is_winner(row, col) || is_winner(row - row_step, col - col_step) {
+                        continue;
+                    }
+
+                    let length = (0..)
+                        .take_while(|&i| is_winner(row + row_step * i, col + col_step * i))
+                        .count();
+
+                    if length >= self.win_length() {
+                        return Some(WinningLine {
+                            start: (row as usize, col as usize),
+                            direction: (row_step, col_step),
+                            length,
+                        });
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.mask().count_ones() as usize == self.cells()
+    }
+
+    pub fn is_blocked(&self) -> bool {
+        (0..self.width()).all(|col| !self.can_play(col) && !self.can_pop(col))
+    }
+
+    pub fn cells(&self) -> usize {
+        self.width() * self.height()
+    }
+
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    pub fn mirrored(&self) -> Self {
+        let mut mirrored = Self {
+            red: 0,
+            yellow: 0,
+            ..*self
+        };
+
+        for col in 0..self.width() {
+            let from = col * self.column_size();
+            let to = (self.width() - 1 - col) * self.column_size();
+
+            mirrored.red |= ((self.red >> from) & self.column_mask(0)) << to;
+            mirrored.yellow |= ((self.yellow >> from) & self.column_mask(0)) << to;
+        }
+
+        mirrored
+    }
+
+    pub fn current_player(&self) -> bool {
+        self.moves.is_multiple_of(2)
+    }
+
+    pub(crate) fn key(&self) -> u128 {
+        self.current() + self.mask()
+    }
+
+    pub(crate) fn possible(&self) -> u128 {
+        (self.mask() + self.bottom_mask) & self.board_mask
+    }
+
+    pub fn can_win_next(&self) -> bool {
+        self.winning_cells(self.current()) & self.possible() != 0
+    }
+
+    pub(crate) fn winning_moves(&self, red: bool) -> u128 {
+        let coins = if red { self.red } else { self.yellow };
+        self.winning_cells(coins) & self.possible()
+    }
+
+    pub fn threat_cells(&self, red: bool) -> Vec<(usize, usize)> {
+        let coins = if red { self.red } else { self.yellow };
+        let cells = self.winning_cells(coins);
+
+        (0..self.width())
+            .flat_map(|col| (0..self.height()).map(move |row| (row, col)))
+            .filter(|&(row, col)| cells & self.cell_mask(row, col) != 0)
+            .collect()
+    }
+
+    pub fn line_through(&self, row: usize, col: usize, coin: Coin) -> Option<(isize, isize)> {
+        let (height, width) = (self.height() as isize, self.width() as isize);
+        let is_coin = |row: isize, col: isize| {
+            (0..height).contains(&row)
+                && (0..width).contains(&col)
+                && self.cell(row as usize, col as usize) == coin
+        };
+        let count = |row_step: isize, col_step: isize| {
+            (1..)
+                .take_while(|&i| is_coin(row as isize + row_step * i, col as isize + col_step * i))
+                .count()
+        };
+
+        [(0, 1), (1, 0), (1, 1), (1, -1)]
+            .into_iter()
+            .find(|&(row_step, col_step)| {
+                count(row_step, col_step) + count(-row_step, -col_step) + 1 >= self.win_length()
+            })
+    }
+
+    pub(crate) fn possible_non_losing_moves(&self) -> u128 {
+        let mut possible = self.possible();
+        let opponent_wins = self.winning_cells(self.current() ^ self.mask());
+        let forced_moves = possible & opponent_wins;
+
+        if forced_moves != 0 {
+            if forced_moves & (forced_moves - 1) != 0 {
+                return 0;
+            }
+
+            possible = forced_moves;
+        }
+
+        possible & !(opponent_wins >> 1)
+    }
+
+    pub(crate) fn move_score(&self, placed: u128) -> u32 {
+        self.winning_cells(self.current() | placed).count_ones()
+    }
+
+    pub(crate) fn column_mask(&self, col: usize) -> u128 {
+        ((1 << self.height()) - 1) << (col * self.column_size())
+    }
+
+    fn current(&self) -> u128 {
+        if self.current_player() {
+            self.red
+        } else {
+            self.yellow
+        }
+    }
+
+    fn mask(&self) -> u128 {
+        self.red | self.yellow
+    }
+
+    fn column_size(&self) -> usize {
+        self.height() + 1
+    }
+
+    fn cell_mask(&self, row: usize, col: usize) -> u128 {
+        1 << (col * self.column_size() + self.height() - 1 - row)
+    }
+
+    fn bottom_mask(&self, col: usize) -> u128 {
+        1 << (col * self.column_size())
+    }
+
+    fn top_mask(&self, col: usize) -> u128 {
+        1 << (col * self.column_size() + self.height() - 1)
+    }
+
+    fn has_line(&self, coins: u128) -> bool {
+        let size = self.column_size();
+
+        for shift in [1, size - 1, size, size + 1] {
+            let mut line = coins;
+            let mut length = 1;
+
+            while length < self.win_length() {
+                let step = length.min(self.win_length() - length);
+                line &= shift_down(line, step * shift);
+                length += step;
+            }
+
+            if line != 0 {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn winning_cells(&self, coins: u128) -> u128 {
+        let size = self.column_size();
+        let length = self.win_length();
+        let mut cells = 0;
+        let mut above = [0; MAX_WIN_LENGTH];
+
+        for shift in [1, size - 1, size, size + 1] {
+            above[0] = self.board_mask;
+
+            for i in 1..length {
+                above[i] = above[i - 1] & shift_down(coins, i * shift);
+            }
+
+            let mut below = self.board_mask;
+
+            for i in 0..length {
+                if i > 0 {
+                    below &= shift_up(coins, i * shift);
+                }
+
+                cells |= below & above[length - 1 - i];
+            }
+        }
+
+        cells & (self.board_mask ^ self.mask())
+    }
+}
+
+fn shift_up(coins: u128, distance: usize) -> u128 {
+    coins.checked_shl(distance as u32).unwrap_or(0)
+}
+
+fn shift_down(coins: u128, distance: usize) -> u128 {
+    coins.checked_shr(distance as u32).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::Board;
+    use crate::coin::Coin;
+    use crate::rules::Rules;
+
+    fn popout_rules() -> Rules {
+        Rules {
+            pop_out: true,
+            ..Rules::default()
+        }
+    }
+
+    #[test]
+    fn pop_shifts_the_column_down() {
+        let mut board = Board::with_rules(popout_rules());
+        board.play_moves("1112").unwrap();
+        let mut position = *board.position();
+        position.pop(0);
+
+        assert_eq!(position.cell(5, 0), Coin::Yellow);
+        assert_eq!(position.cell(4, 0), Coin::Red);
+        assert_eq!(position.cell(3, 0), Coin::Empty);
+        assert_eq!(position.cell(5, 1), Coin::Yellow);
+        assert_eq!(position.moves(), 5);
+        assert!(!position.current_player());
+    }
+
+    #[test]
+    fn can_pop_only_own_bottom_coin() {
+        let mut board = Board::with_rules(popout_rules());
+        board.play_moves("12").unwrap();
+        let position = board.position();
+
+        assert!(position.can_pop(0));
+        assert!(!position.can_pop(1));
+        assert!(!position.can_pop(2));
+        assert!(!Board::from_moves("12").unwrap().position().can_pop(0));
+    }
+
+    #[test]
+    fn popper_wins_when_both_sides_get_a_line() {
+        let mut board =
+            Board::from_grid_with_rules("7/7/7/r6/yrrr3/ryyy2y r", popout_rules()).unwrap();
+        board.pop(0).unwrap();
+
+        assert!(board.position().has_line(board.position().red));
+        assert!(board.position().has_line(board.position().yellow));
+        assert_eq!(board.position().winner(), Some(Coin::Red));
+    }
+}
@@ -0,0 +1,246 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::board::Board;
+use crate::error::{NotationError, RecordError};
+use crate::game_move::Move;
+use crate::game_state::GameState;
+use crate::rules::{self, Rules};
+
+const MOVES_PER_LINE: usize = 8;
+const SECONDS_PER_DAY: u64 = 86_400;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GameRecord {
+    headers: Vec<(String, String)>,
+    moves: Vec<Move>,
+}
+
+impl GameRecord {
+    pub fn new(board: &Board) -> Self {
+        let rules = board.rules();
+        let start = board.start_position();
+        let mut record = Self {
+            headers: Vec::new(),
+            moves: board.history().to_vec(),
+        };
+
+        record.set_header("Date", &today());
+        record.set_header("Size", &format!("{}x{}", rules.width, rules.height));
+        record.set_header("Connect", &rules.win_length.to_string());
+
+        if rules.pop_out {
+            record.set_header("PopOut", "yes");
+        }
+
+        if start.moves() > 0 {
+            record.set_header("Position", &Board::from_position(*start).to_grid());
+        }
+
+        record.set_header("Result", result(board.game_state()));
+        record
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        match self.headers.iter_mut().find(|(key, _)| key == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.headers.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn rules(&self) -> Result<Rules, RecordError> {
+        let mut rules = Rules::default();
+
+        if let Some(size) = self.header("Size") {
+            (rules.width, rules.height) =
+                rules::parse_size(size).ok_or(RecordError::InvalidRules)?;
+        }
+
+        if let Some(length) = self.header("Connect") {
+            rules.win_length = length.parse().map_err(|_| RecordError::InvalidRules)?;
+        }
+
+        rules.pop_out = self.header("PopOut") == Some("yes");
+
+        if !rules.is_valid() {
+            return Err(RecordError::InvalidRules);
+        }
+
+        Ok(rules)
+    }
+
+    pub fn to_board(&self) -> Result<Board, RecordError> {
+        let rules = self.rules()?;
+        let mut board = match self.header("Position") {
+            Some(grid) => Board::from_grid_with_rules(grid, rules)?,
+            None => Board::with_rules(rules),
+        };
+
+        for (index, &mv) in self.moves.iter().enumerate() {
+            board
+                .play_move(mv)
+                .map_err(|error| NotationError::IllegalMove { index, error })?;
+        }
+
+        Ok(board)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, RecordError> {
+        let mut record = Self {
+            headers: Vec::new(),
+            moves: Vec::new(),
+        };
+        let mut lines = text.lines().map(str::trim).peekable();
+
+        while let Some(line) = lines.next_if(|line| line.is_empty() || line.starts_with('[')) {
+            if line.is_empty() {
+                continue;
+            }
+
+            let (name, value) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+                .and_then(|line| line.split_once(' '))
+                .and_then(|(name, value)| Some((name, value.strip_prefix('"')?.strip_suffix('"')?)))
+                .ok_or_else(|| RecordError::InvalidHeader(line.to_string()))?;
+
+            record.set_header(name, value);
+        }
+
+        for token in lines.flat_map(str::split_whitespace) {
+            if token.ends_with('.') || is_result(token) {
+                continue;
+            }
+
+            let mv =
+                Move::parse(token).ok_or_else(|| RecordError::InvalidMove(token.to_string()))?;
+            record.moves.push(mv);
+        }
+
+        Ok(record)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RecordError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RecordError> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, value) in &self.headers {
+            writeln!(f, "[{name} \"{value}\"]")?;
+        }
+
+        writeln!(f)?;
+
+        for (i, pair) in self.moves.chunks(2).enumerate() {
+            let moves = pair.iter().map(Move::to_string).collect::<Vec<_>>();
+            let separator = if (i + 1) % MOVES_PER_LINE == 0 {
+                "\n"
+            } else {
+                " "
+            };
+
+            write!(f, "{}. {}{}", i + 1, moves.join(" "), separator)?;
+        }
+
+        writeln!(f, "{}", self.header("Result").unwrap_or("*"))
+    }
+}
+
+fn result(state: &GameState) -> &'static str {
+    match state {
+        GameState::RedWon => "1-0",
+        GameState::YellowWon => "0-1",
+        GameState::Draw => "1/2-1/2",
+        GameState::OnGoing => "*",
+    }
+}
+
+fn is_result(token: &str) -> bool {
+    ["1-0", "0-1", "1/2-1/2", "*"].contains(&token)
+}
+
+fn today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+
+    // Converts days since 1970-01-01 to a civil date, see Howard Hinnant's date algorithms.
+    let days = (seconds / SECONDS_PER_DAY) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}.{month:02}.{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_round_trip(board: &Board) {
+        let record = GameRecord::new(board);
+        let parsed = GameRecord::parse(&record.to_string()).unwrap();
+        let rebuilt = parsed.to_board().unwrap();
+
+        assert_eq!(parsed, record);
+        assert_eq!(rebuilt.history(), board.history());
+        assert!(rebuilt.position() == board.position());
+        assert!(rebuilt.start_position() == board.start_position());
+        assert!(rebuilt.game_state() == board.game_state());
+    }
+
+    #[test]
+    fn standard_game_round_trips() {
+        assert_round_trip(&Board::from_moves("").unwrap());
+        assert_round_trip(&Board::from_moves("4455667").unwrap());
+        assert_round_trip(&Board::from_moves("2252576253462244111563365343671351441").unwrap());
+    }
+
+    #[test]
+    fn popout_game_round_trips() {
+        let mut board = Board::with_rules(Rules {
+            pop_out: true,
+            ..Rules::new(8, 7)
+        });
+        board.play_moves("1121p1p1343").unwrap();
+
+        assert_round_trip(&board);
+        assert!(GameRecord::new(&board).rules().unwrap().pop_out);
+    }
+
+    #[test]
+    fn position_game_round_trips() {
+        let mut board = Board::from_grid("7/7/7/7/3y3/2rr3 y").unwrap();
+        board.play_moves("3526").unwrap();
+        let record = GameRecord::new(&board);
+
+        assert_eq!(record.header("Position"), Some("7/7/7/7/3y3/2rr3 y"));
+        assert_round_trip(&board);
+    }
+}
@@ -0,0 +1,55 @@
+use crate::position::{BOARD_HEIGHT, BOARD_WIDTH};
+
+pub const WIN_LENGTH: usize = 4;
+pub const MIN_SIZE: usize = 4;
+pub const MIN_WIN_LENGTH: usize = 2;
+pub const MAX_WIDTH: usize = MAX_BITS / (MIN_SIZE + 1);
+
+const MAX_BITS: usize = 128;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    pub width: usize,
+    pub height: usize,
+    pub win_length: usize,
+    pub pop_out: bool,
+}
+
+impl Rules {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            win_length: WIN_LENGTH,
+            pop_out: false,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.width >= MIN_SIZE
+            && self.height >= MIN_SIZE
+            && self.width * (self.height + 1) <= MAX_BITS
+            && self.win_length >= MIN_WIN_LENGTH
+            && self.win_length <= self.width.max(self.height)
+    }
+
+    pub fn is_standard(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self::new(BOARD_WIDTH, BOARD_HEIGHT)
+    }
+}
+
+pub fn parse_size(text: &str) -> Option<(usize, usize)> {
+    let (width, height) = text.split_once('x')?;
+    let width = width.parse().ok()?;
+    let height = height.parse().ok()?;
+
+    Rules::new(width, height)
+        .is_valid()
+        .then_some((width, height))
+}
@@ -0,0 +1,59 @@
+use std::mem;
+
+use crate::game_move::Move;
+
+const HASH_MULTIPLIER: u64 = 0x9E37_79B9_7F4A_7C15;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Clone, Copy)]
+pub struct Entry {
+    pub key: u128,
+    pub depth: usize,
+    pub bound: Bound,
+    pub score: f64,
+    pub best_move: Move,
+}
+
+pub struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+}
+
+impl TranspositionTable {
+    pub fn with_memory(bytes: usize) -> Self {
+        let size = (bytes / mem::size_of::<Option<Entry>>()).max(1);
+
+        Self {
+            entries: vec![None; size],
+        }
+    }
+
+    pub fn get(&self, key: u128) -> Option<&Entry> {
+        self.entries[self.index(key)]
+            .as_ref()
+            .filter(|entry| entry.key == key)
+    }
+
+    pub fn put(&mut self, entry: Entry) {
+        let index = self.index(entry.key);
+
+        if let Some(old) = &self.entries[index] {
+            if old.key == entry.key && old.depth > entry.depth {
+                return;
+            }
+        }
+
+        self.entries[index] = Some(entry);
+    }
+
+    fn index(&self, key: u128) -> usize {
+        let folded = key as u64 ^ (key >> 64) as u64;
+        let hash = folded.wrapping_mul(HASH_MULTIPLIER);
+        ((hash as u128 * self.entries.len() as u128) >> 64) as usize
+    }
+}